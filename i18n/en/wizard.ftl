@@ -1,5 +1,6 @@
 app-title = Wizard
 about = About
+settings = Settings
 view = View
 select-file = Add installers
 install-file = Install all
@@ -14,3 +15,267 @@ description = Description
 url = Url
 license = License
 size = Size
+maintainer = Maintainer
+section = Section
+priority = Priority
+installed-size = Installed size
+homepage = Homepage
+multi-arch = Multi-Arch
+pre-depends = Pre-Depends
+depends = Depends
+recommends = Recommends
+conflicts = Conflicts
+breaks = Breaks
+replaces = Replaces
+provides = Provides
+from-system = Needed from the system
+level-notice = Notice
+level-warning = Warning
+level-danger = Danger
+clash-conflicts = Conflicts with { $package } { $version }, which will be removed ({ $relation })
+clash-conflicted-by = The installed { $package } { $version } conflicts with this package and will be removed ({ $relation })
+clash-breaks = Breaks { $package } { $version }, which must be upgraded or will be removed ({ $relation })
+clash-broken-by = The installed { $package } { $version } does not work with this version and must be upgraded or will be removed ({ $relation })
+clash-replaces = Takes over files of { $package } { $version } ({ $relation })
+tab-details = Details
+tab-files = Files
+search-files = Search files
+file-count = { $files ->
+    [one] { $files } file
+   *[other] { $files } files
+}
+files-existing = { $existing ->
+    [one] { $existing } already exists on the system and will be overwritten
+   *[other] { $existing } already exist on the system and will be overwritten
+}
+file-exists = Already on the system
+setuid = Runs with its owner's rights
+link = { $name } → { $target }
+contents-failed = The files of this package could not be listed.
+uses-selected = Uses { $packages } from the selection
+selection-mismatch = { $package } needs { $relation }, but the selected { $provider } is version { $version }.
+cancel = Cancel
+remove = Remove
+retry = Retry
+loading = Loading…
+installation-cancelled = Installation cancelled
+installation-failed = Installation failed
+refresh-failed = Refreshing the package lists failed
+enable-arch-failed = Enabling the architecture failed
+enable-source-failed = Enabling the software source failed
+error-code = Error code: { $code }
+copy-details = Copy details
+dismiss = Dismiss
+add = Add
+missing-dependencies = Missing dependencies
+needed-by = Needed by { $package }
+needed-by-reason = Needed by { $package }, but { $reason }
+disabled-sources = Disabled software sources (unverified)
+disabled-sources-unverified = These sources were not checked for the missing packages, so enabling one may not help.
+installers-nearby = Installers found next to the selected files
+enable = Enable
+enable-source-question = Enable “{ $description }” ({ $id })? It is not known to provide the missing packages. The package lists will be refreshed and the installation checked again.
+enabling-source = Enabling the software source and refreshing the package lists…
+action-install = New install
+action-upgrade = Upgrade from { $installed }
+action-reinstall = Same version installed
+action-downgrade = Downgrade from { $installed }
+action-from-repository = Install { $version } from { $origin }
+repository-available = { $origin } has version { $version } of this package. Installing it from there keeps it updated.
+repository-chosen = Version { $version } will be installed from { $origin } instead of this file.
+use-repository = Use repository
+use-file = Use file
+arch-not-enabled = This package is for { $arch }, which this { $native } system can run once it is enabled.
+arch-unsupported = This package is for { $arch } and cannot run on this { $native } system.
+enable-arch = Enable { $arch }
+enabling-arch = Enabling { $arch } and refreshing package lists…
+confirm-title = Review the changes
+simulating = Checking what will change…
+no-other-changes = Only the selected packages will be installed.
+will-install = Extra packages to install
+will-upgrade = Will be upgraded
+will-downgrade = Will be downgraded
+will-remove = Will be removed
+preview-version = { $version } from { $origin }
+preview-replaces = { $installed } → { $version } from { $origin }
+download-size = { $size } to download
+disk-usage = { $size } of disk space used
+auth-not-required = No administrator password is needed.
+auth-required = You will be asked for an administrator password.
+auth-denied = Your account is not allowed to install software.
+confirm-install = Review changes before installing
+refresh-lists = Refresh package lists before installing
+refresh-never = Never
+refresh-after-hours = { $hours ->
+    [one] When older than an hour
+   *[other] When older than { $hours } hours
+}
+refresh-after-days = { $days ->
+    [one] When older than a day
+   *[other] When older than { $days } days
+}
+
+status-waiting = Waiting for other software operations to finish
+status-waiting-for-auth = Waiting for authentication
+status-setup = Preparing
+status-loading-cache = Loading package information
+status-dep-resolve = Resolving dependencies
+status-download = Downloading
+status-checking = Checking packages
+status-install = Installing
+status-update = Updating
+status-remove = Removing
+status-cleanup = Cleaning up
+status-run-hook = Running package scripts
+status-cancel = Cancelling
+status-finished = Finished
+status-running = Working
+speed = { $size }/s
+size-bytes = { $size ->
+    [one] { $size } byte
+   *[other] { $size } bytes
+}
+size-kb = { $size } kB
+size-mb = { $size } MB
+size-gb = { $size } GB
+time-remaining = { $time } remaining
+time-elapsed = { $time } elapsed
+
+error-unknown = An unknown error occurred.
+error-unknown-action = Copy the details below and report the problem.
+error-out-of-memory = The system ran out of memory.
+error-out-of-memory-action = Close some applications and try again.
+error-no-network = There is no network connection.
+error-no-network-action = Check your network connection and try again.
+error-not-supported = This action is not supported by the package system.
+error-not-supported-action = Try installing the package with a different tool.
+error-internal-error = The package system encountered an internal error.
+error-internal-error-action = Try again. If the problem persists, copy the details below and report it.
+error-gpg-failure = A security signature could not be verified.
+error-gpg-failure-action = Make sure the signing keys of your software sources are installed and up to date.
+error-package-id-invalid = The package identifier is not valid.
+error-package-id-invalid-action = Make sure the package file is not damaged and try again.
+error-package-not-installed = The package is not installed.
+error-package-not-installed-action = No action is needed if you meant to remove it.
+error-package-not-found = The package could not be found.
+error-package-not-found-action = Refresh your software sources and try again.
+error-package-already-installed = The package is already installed.
+error-package-already-installed-action = No action is needed. To reinstall it, install the file again and confirm the reinstall when asked.
+error-package-download-failed = A package could not be downloaded.
+error-package-download-failed-action = Check your network connection and try again.
+error-group-not-found = The package group could not be found.
+error-group-not-found-action = Refresh your software sources and try again.
+error-group-list-invalid = The list of package groups is not valid.
+error-group-list-invalid-action = Refresh your software sources and try again.
+error-dep-resolution-failed = Some dependencies of the package could not be satisfied.
+error-dep-resolution-failed-action = Enable the software sources that provide the missing packages, or add their installers as well.
+error-filter-invalid = The package search filter is not valid.
+error-filter-invalid-action = Copy the details below and report the problem.
+error-create-thread-failed = The package system could not start a task.
+error-create-thread-failed-action = Restart your computer and try again.
+error-transaction-error = The package operation failed.
+error-transaction-error-action = Try again. If the problem persists, copy the details below and report it.
+error-transaction-cancelled = The operation was cancelled.
+error-transaction-cancelled-action = Start the installation again when you are ready.
+error-no-cache = Package information is not available.
+error-no-cache-action = Refresh your software sources and try again.
+error-repo-not-found = A software source could not be found.
+error-repo-not-found-action = Check your software sources in the system settings.
+error-cannot-remove-system-package = A package required by the system cannot be removed.
+error-cannot-remove-system-package-action = Keep the package installed, or remove it from a terminal if you are sure.
+error-process-kill = The package operation was stopped by force.
+error-process-kill-action = Try again and let the operation finish.
+error-failed-initialization = The package system could not be started.
+error-failed-initialization-action = Restart your computer and try again.
+error-failed-finalise = The package system could not finish the operation.
+error-failed-finalise-action = Restart your computer and try again.
+error-failed-config-parsing = The package system configuration could not be read.
+error-failed-config-parsing-action = Check the package manager configuration files for mistakes.
+error-cannot-cancel = The operation cannot be cancelled at this point.
+error-cannot-cancel-action = Wait for the operation to finish.
+error-cannot-get-lock = Another application is using the package system.
+error-cannot-get-lock-action = Wait for other software installations or updates to finish, then try again.
+error-no-packages-to-update = There are no packages to update.
+error-no-packages-to-update-action = No action is needed.
+error-cannot-write-repo-config = The software source configuration could not be saved.
+error-cannot-write-repo-config-action = Check that you have permission to change software sources.
+error-local-install-failed = The package file could not be installed.
+error-local-install-failed-action = Make sure the file is a valid package for this system.
+error-bad-gpg-signature = A security signature is not valid.
+error-bad-gpg-signature-action = Do not install this software unless you trust where it came from.
+error-missing-gpg-signature = A package is not signed.
+error-missing-gpg-signature-action = Only install unsigned packages if you trust where they came from.
+error-cannot-install-source-package = Source packages cannot be installed.
+error-cannot-install-source-package-action = Download the binary package for your system instead.
+error-repo-configuration-error = A software source is not configured correctly.
+error-repo-configuration-error-action = Check your software sources in the system settings.
+error-no-license-agreement = The license agreement was not accepted.
+error-no-license-agreement-action = Accept the license agreement to install this software.
+error-file-conflicts = The package contains files that belong to another installed package.
+error-file-conflicts-action = Remove the conflicting package first, or contact the package publisher.
+error-package-conflicts = The package conflicts with another installed package.
+error-package-conflicts-action = Remove the conflicting package first, then try again.
+error-repo-not-available = A software source is not available.
+error-repo-not-available-action = Check your network connection, or disable the software source.
+error-invalid-package-file = The file is not a valid package.
+error-invalid-package-file-action = Download the installer again from a trusted source.
+error-package-install-blocked = Installing this package is blocked by the system.
+error-package-install-blocked-action = Ask your administrator to allow this package.
+error-package-corrupt = The package file is damaged.
+error-package-corrupt-action = Download the installer again.
+error-all-packages-already-installed = All packages are already installed.
+error-all-packages-already-installed-action = No action is needed.
+error-file-not-found = The package file could not be found.
+error-file-not-found-action = Make sure the file has not been moved or deleted, then add it again.
+error-no-more-mirrors-to-try = No download server could provide the package.
+error-no-more-mirrors-to-try-action = Check your network connection and try again later.
+error-no-distro-upgrade-data = No system upgrade information is available.
+error-no-distro-upgrade-data-action = Try again later.
+error-incompatible-architecture = The package is not built for this computer's architecture.
+error-incompatible-architecture-action = Download the installer made for your system's architecture.
+error-no-space-on-device = There is not enough disk space.
+error-no-space-on-device-action = Free up some disk space and try again.
+error-media-change-required = A different installation medium is required.
+error-media-change-required-action = Insert the requested disc or drive and try again.
+error-not-authorized = You are not allowed to install software.
+error-not-authorized-action = Ask an administrator to install the package, or sign in as an administrator.
+error-update-not-found = The update could not be found.
+error-update-not-found-action = Refresh your software sources and try again.
+error-cannot-install-repo-unsigned = Software from unsigned sources cannot be installed.
+error-cannot-install-repo-unsigned-action = Only use software sources you trust, and install their signing keys.
+error-cannot-update-repo-unsigned = Software from unsigned sources cannot be updated.
+error-cannot-update-repo-unsigned-action = Only use software sources you trust, and install their signing keys.
+error-cannot-get-filelist = The list of files in the package could not be read.
+error-cannot-get-filelist-action = Download the installer again.
+error-cannot-get-requires = The package requirements could not be determined.
+error-cannot-get-requires-action = Refresh your software sources and try again.
+error-cannot-disable-repository = The software source could not be disabled.
+error-cannot-disable-repository-action = Check your software sources in the system settings.
+error-restricted-download = The download is not allowed on the current network.
+error-restricted-download-action = Connect to a different network and try again.
+error-package-failed-to-configure = The package could not be configured.
+error-package-failed-to-configure-action = Copy the details below and report the problem to the package publisher.
+error-package-failed-to-build = The package could not be built.
+error-package-failed-to-build-action = Copy the details below and report the problem to the package publisher.
+error-package-failed-to-install = The package could not be installed.
+error-package-failed-to-install-action = Copy the details below and report the problem to the package publisher.
+error-package-failed-to-remove = The package could not be removed.
+error-package-failed-to-remove-action = Copy the details below and report the problem to the package publisher.
+error-failed-due-to-running-process = An application that uses the package is still running.
+error-failed-due-to-running-process-action = Close the application and try again.
+error-package-database-changed = The package database changed during the operation.
+error-package-database-changed-action = Try again.
+error-provide-type-not-supported = This kind of search is not supported.
+error-provide-type-not-supported-action = Copy the details below and report the problem.
+error-install-root-invalid = The installation location is not valid.
+error-install-root-invalid-action = Check the package manager configuration.
+error-cannot-fetch-sources = The package sources could not be downloaded.
+error-cannot-fetch-sources-action = Check your network connection and try again.
+error-cancelled-priority = The operation was cancelled to make room for a more important one.
+error-cancelled-priority-action = Try again in a moment.
+error-unfinished-transaction = A previous package operation did not finish.
+error-unfinished-transaction-action = Repair the package system, for example with “sudo dpkg --configure -a”, then try again.
+error-lock-required = The package system must be locked for this operation.
+error-lock-required-action = Close other package managers and try again.
+error-repo-already-set = The software source is already set up.
+error-repo-already-set-action = No action is needed.
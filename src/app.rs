@@ -1,24 +1,42 @@
 // SPDX-License-Identifier: {{LICENSE}}
 
+use crate::arch::{self, Architectures, Support};
+use crate::backend::{PackageBackend, PackageKitBackend};
 use crate::config::Config;
+use crate::conflict::{self, Clash, Kind, Level};
+use crate::contents::{Contents, Entry, EntryKind};
+use crate::dpkg::{self, Database};
+use crate::errors;
 use crate::fl;
-use crate::package::{install_packages_local, Package};
-use crate::packagekit::{transaction_handle, PackageKit};
+use crate::format::{format_duration, format_size, format_speed};
+use crate::package::{
+    install_packages_local, Action, DetailsLoader, FileState, Package, PackageFile,
+};
+use crate::packagekit::{
+    finish, Error, Exit, PackageId, Repository, TransactionError, TransactionEvent,
+};
+use crate::polkit::Authorization;
+use crate::preview::{self, Preview, PreviewPackage};
+use crate::progress::{status_text, Progress};
+use crate::selection::{self, Dependency, Provider};
+use crate::unmet::{self, Diagnosis};
 use ashpd::desktop::file_chooser::{FileFilter, SelectedFiles};
 use cosmic::app::{Command, Core};
 use cosmic::cosmic_config::{self, CosmicConfigEntry};
 use cosmic::iced::alignment::{Horizontal, Vertical};
-use cosmic::iced::{futures, subscription, Alignment, Length, Subscription};
+use cosmic::iced::futures::channel::mpsc;
+use cosmic::iced::{subscription, Alignment, Length, Subscription};
 use cosmic::prelude::CollectionWidget;
-use cosmic::widget::{self, menu, row, settings, ProgressBar};
+use cosmic::widget::{self, menu, row, segmented_button, settings, ProgressBar};
 use cosmic::{command, cosmic_theme, theme, Application, ApplicationExt, Element};
-use futures_util::SinkExt;
+use futures_util::{SinkExt, StreamExt};
 use std::any::TypeId;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::future::pending;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 const REPOSITORY: &str = "https://github.com/cosmic-utils/wizard";
 const APP_ICON: &[u8] = include_bytes!("../res/icons/hicolor/scalable/apps/icon.svg");
@@ -34,12 +52,64 @@ pub struct AppModel {
     key_binds: HashMap<menu::KeyBind, MenuAction>,
     // Configuration data that persists between application runs.
     config: Config,
-
-    packages: Vec<Package>,
+    /// Writes changes to the configuration, if it could be opened.
+    config_handler: Option<cosmic_config::Config>,
+    /// The package manager used to inspect and install packages.
+    backend: Arc<dyn PackageBackend>,
+    /// Reads the details of added files in the background.
+    loader: DetailsLoader,
+
+    /// The files added by the user, in the order they were added.
+    files: Vec<PackageFile>,
+    /// The packages of every loaded file, collected again whenever the files change.
+    selected: Vec<Package>,
+    /// How the selected packages depend on each other.
+    dependencies: Vec<Dependency>,
     package: Option<Package>,
+    /// The tabs of the details view.
+    details_tabs: segmented_button::SingleSelectModel,
+    /// The files of the package in the details view, once read.
+    contents: Option<Result<Arc<Contents>, TransactionError>>,
+    /// The directories of the files tab the user collapsed.
+    collapsed: HashSet<String>,
+    /// What the files tab is filtered by.
+    contents_search: String,
     is_installed: bool,
+    is_cancelled: bool,
     ask_install: bool,
-    progress: Option<f32>,
+    /// Whether the age of the package lists was checked for the install being asked for,
+    /// so it is not checked again until that install starts or is called off.
+    cache_checked: bool,
+    /// Whether the package lists are being refreshed before the install.
+    is_refreshing: bool,
+    /// The choices of the package list age setting, one for each of [`CACHE_AGES`].
+    cache_age_labels: Vec<String>,
+    /// Whether the user is being asked to confirm the install.
+    confirm_install: bool,
+    /// What the install would change, once the simulation has finished.
+    preview: Option<Preview>,
+    /// Whether the install needs the user to authenticate, once polkit has answered.
+    authorization: Option<Authorization>,
+    allow_cancel: bool,
+    progress: Option<Progress>,
+    /// Why the last operation failed, until the user dismisses it.
+    error: Option<TransactionError>,
+    /// What failed with [`AppModel::error`], which titles it.
+    failed: Operation,
+    /// What could fix unmet dependencies, if that is why the installation failed.
+    diagnosis: Option<Diagnosis>,
+    /// What dpkg has installed, once read, to find the clashes of selected packages.
+    status: Option<Arc<Database>>,
+    /// How the package in the details view clashes with what dpkg has installed.
+    clashes: Vec<Clash>,
+    /// The architectures dpkg installs packages for, once detected.
+    architectures: Option<Architectures>,
+    /// The foreign architecture being added to dpkg, if any.
+    enabling_architecture: Option<String>,
+    /// The disabled software source the user asked to enable, until they confirm.
+    enable_repository: Option<Repository>,
+    /// Whether a software source is being enabled and the package lists refreshed.
+    is_enabling: bool,
 }
 
 /// Messages emitted by the application and its widgets.
@@ -48,13 +118,44 @@ pub enum Message {
     OpenRepositoryUrl,
     ToggleContextPage(ContextPage),
     UpdateConfig(Config),
+    SetConfirmInstall(bool),
+    SetMaxCacheAge(u32),
     SelectFile,
     ProcessSelectedFiles(Vec<String>),
     UpdatePackages(String),
+    PackagesLoaded(String, Result<Vec<Package>, TransactionError>),
+    RetryFile(String),
+    RemoveFile(String),
+    UseRepository(String, PackageId, bool),
+    StatusRead(Arc<Database>),
+    ArchitecturesDetected(Architectures),
+    EnableArchitecture(String),
+    ArchitectureEnabled(String, Result<(), TransactionError>),
     AskInstallation,
-    Progress(u32),
-    PackagesInstalled(bool),
+    CacheChecked(bool),
+    CacheRefreshed(Result<(), TransactionError>),
+    PreviewLoaded(Result<Preview, TransactionError>),
+    AuthorizationChecked(Authorization),
+    ConfirmInstallation,
+    CancelConfirmation,
+    Progress(TransactionEvent),
+    AllowCancel(bool),
+    CancelInstallation,
+    InstallationCancelled,
+    InstallationFailed(TransactionError),
+    PackagesInstalled,
+    Diagnosed(Diagnosis),
+    EnableRepository(Repository),
+    CancelEnableRepository,
+    ConfirmEnableRepository,
+    RepositoryEnabled(Result<(), TransactionError>),
+    CopyErrorDetails,
+    DismissError,
     ShowDetails(Box<Package>),
+    SelectDetailsTab(segmented_button::Entity),
+    ContentsLoaded(String, Result<Arc<Contents>, TransactionError>),
+    ToggleDirectory(String),
+    SearchContents(String),
 }
 
 /// Create a COSMIC application from the app model
@@ -91,34 +192,42 @@ impl Application for AppModel {
             }
         }
 
-        // Construct the app model with the runtime core.
-        let mut app = AppModel {
-            core,
-            context_page: ContextPage::default(),
-            key_binds: HashMap::new(),
-            // Optional configuration file for an application.
-            config: cosmic_config::Config::new(Self::APP_ID, Config::VERSION)
-                .map(|context| {
-                    Config::get_entry(&context).unwrap_or_else(|(_errors, config)| {
-                        // for why in errors {
-                        //     tracing::error!(%why, "error loading app config");
-                        // }
-
-                        config
-                    })
+        // Optional configuration file for an application.
+        let config_handler = cosmic_config::Config::new(Self::APP_ID, Config::VERSION).ok();
+
+        let config = config_handler
+            .as_ref()
+            .map(|context| {
+                Config::get_entry(context).unwrap_or_else(|(_errors, config)| {
+                    // for why in errors {
+                    //     tracing::error!(%why, "error loading app config");
+                    // }
+
+                    config
                 })
-                .unwrap_or_default(),
+            })
+            .unwrap_or_default();
 
-            packages: Vec::new(),
-            package: None,
-            is_installed: false,
-            ask_install: false,
-            progress: None,
-        };
+        // Construct the app model with the runtime core.
+        let mut app = AppModel::new(core, config, config_handler);
 
         // Create a startup command that sets the window title.
         commands.push(app.update_title());
 
+        commands.push(read_status());
+        commands.push(Command::perform(
+            Architectures::detect(),
+            |result| match result {
+                Ok(architectures) => {
+                    cosmic::app::Message::App(Message::ArchitecturesDetected(architectures))
+                }
+                Err(why) => {
+                    eprintln!("failed to detect architectures: {why:#}");
+                    cosmic::app::Message::None
+                }
+            },
+        ));
+
         (app, Command::batch(commands))
     }
 
@@ -130,6 +239,7 @@ impl Application for AppModel {
 
         Some(match self.context_page {
             ContextPage::About => self.about(),
+            ContextPage::Settings => self.settings(),
         })
     }
 
@@ -139,7 +249,10 @@ impl Application for AppModel {
             menu::root(fl!("view")),
             menu::items(
                 &self.key_binds,
-                vec![menu::Item::Button(fl!("about"), MenuAction::About)],
+                vec![
+                    menu::Item::Button(fl!("settings"), MenuAction::Settings),
+                    menu::Item::Button(fl!("about"), MenuAction::About),
+                ],
             ),
         )]);
 
@@ -153,6 +266,7 @@ impl Application for AppModel {
     /// beginning of the application, and persist through its lifetime.
     fn subscription(&self) -> Subscription<Self::Message> {
         struct ProgressSubscription;
+        struct RefreshSubscription;
 
         let mut subscriptions =
             vec![self
@@ -167,35 +281,26 @@ impl Application for AppModel {
                 })];
 
         if self.ask_install {
-            let packages = self.packages.clone();
+            let backend = self.backend.clone();
+            let packages = self.selected.clone();
             subscriptions.push(subscription::channel(
                 TypeId::of::<ProgressSubscription>(),
                 16,
-                move |msg_tx| async move {
-                    let msg_tx = Arc::new(tokio::sync::Mutex::new(msg_tx));
-
-                    let msg_tx1 = msg_tx.clone();
-                    tokio::task::spawn_blocking(move || {
-                        if let Ok(status) = install_packages_local(
-                            packages,
-                            Box::new(move |progress| {
-                                let _ = futures::executor::block_on(async {
-                                    msg_tx1.lock().await.send(Message::Progress(progress)).await
-                                });
-                            }),
-                        ) {
-                            let msg_tx2 = msg_tx.clone();
-                            let _ = futures::executor::block_on(async {
-                                msg_tx2
-                                    .lock()
-                                    .await
-                                    .send(Message::PackagesInstalled(status))
-                                    .await
-                            });
-                        }
-                    })
-                    .await
-                    .unwrap();
+                move |mut msg_tx| async move {
+                    install(backend, packages, &mut msg_tx).await;
+
+                    pending().await
+                },
+            ));
+        }
+
+        if self.is_refreshing {
+            let backend = self.backend.clone();
+            subscriptions.push(subscription::channel(
+                TypeId::of::<RefreshSubscription>(),
+                16,
+                move |mut msg_tx| async move {
+                    refresh(backend, &mut msg_tx).await;
 
                     pending().await
                 },
@@ -233,6 +338,24 @@ impl Application for AppModel {
                 self.config = config;
             }
 
+            Message::SetConfirmInstall(confirm_install) => match &self.config_handler {
+                Some(handler) => {
+                    if let Err(why) = self.config.set_confirm_install(handler, confirm_install) {
+                        eprintln!("failed to save settings: {why}");
+                    }
+                }
+                None => self.config.confirm_install = confirm_install,
+            },
+
+            Message::SetMaxCacheAge(hours) => match &self.config_handler {
+                Some(handler) => {
+                    if let Err(why) = self.config.set_max_cache_age_hours(handler, hours) {
+                        eprintln!("failed to save settings: {why}");
+                    }
+                }
+                None => self.config.max_cache_age_hours = hours,
+            },
+
             Message::SelectFile => {
                 let future = async {
                     if let Ok(request) = SelectedFiles::open_file()
@@ -280,46 +403,369 @@ impl Application for AppModel {
             }
 
             Message::UpdatePackages(path) => {
-                let pk = PackageKit::new().unwrap();
-                let tx = pk.transaction().unwrap();
+                match self.files.iter().find(|file| file.path == path) {
+                    Some(file) if matches!(file.state, FileState::Failed(_)) => {
+                        return self.update(Message::RetryFile(path));
+                    }
+                    Some(_) => (),
+                    None => {
+                        self.files.push(PackageFile::new(path.clone()));
+                        return self.load(path);
+                    }
+                }
+            }
 
-                tx.get_details_local(&[&path]).unwrap();
+            Message::PackagesLoaded(path, result) => {
+                // The file may have been removed while it was loading.
+                if let Some(file) = self.files.iter_mut().find(|file| file.path == path) {
+                    file.state = match result {
+                        Ok(packages) => FileState::Loaded(packages),
+                        Err(error) => {
+                            eprintln!("failed to load package details from {path}: {error}");
+                            FileState::Failed(error)
+                        }
+                    };
+                }
+                self.update_selection();
+            }
 
-                let tx_details = transaction_handle(tx, |_| {}).unwrap();
+            Message::RetryFile(path) => {
+                if let Some(file) = self.files.iter_mut().find(|file| file.path == path) {
+                    file.state = FileState::Loading;
+                    self.update_selection();
+                    return self.load(path);
+                }
+            }
 
-                for tx_detail in tx_details {
-                    self.packages.push(Package::new(path.clone(), tx_detail));
+            Message::RemoveFile(path) => {
+                self.files.retain(|file| file.path != path);
+                self.update_selection();
+                if self
+                    .package
+                    .as_ref()
+                    .is_some_and(|package| package.path == path)
+                {
+                    self.package = None;
                 }
             }
-            Message::Progress(progress) => {
-                // Sometimes it returns 101 at the start
-                if progress > 100 {
-                    self.progress = Some(0.0);
-                } else {
-                    self.progress = Some(progress as f32);
+
+            Message::UseRepository(path, id, use_repository) => {
+                if let Some(FileState::Loaded(packages)) = self
+                    .files
+                    .iter_mut()
+                    .find(|file| file.path == path)
+                    .map(|file| &mut file.state)
+                {
+                    for package in packages.iter_mut().filter(|package| package.id == id) {
+                        package.use_repository = use_repository;
+                    }
                 }
+                self.update_selection();
+            }
+
+            Message::StatusRead(status) => {
+                self.status = Some(status);
+                self.update_clashes();
+            }
+
+            Message::ArchitecturesDetected(architectures) => {
+                self.architectures = Some(architectures);
+            }
+
+            Message::EnableArchitecture(arch) => {
+                self.enabling_architecture = Some(arch.clone());
+                let backend = self.backend.clone();
+                return command::future(async move {
+                    let result = enable_architecture(backend, arch.clone())
+                        .await
+                        .map_err(TransactionError::from);
+                    Message::ArchitectureEnabled(arch, result)
+                });
             }
+
+            Message::ArchitectureEnabled(arch, result) => {
+                self.enabling_architecture = None;
+                match result {
+                    Ok(()) => {
+                        if let Some(architectures) = &mut self.architectures {
+                            architectures.foreign.push(arch);
+                        }
+                        // The package lists were refreshed along the way.
+                        self.cache_checked = true;
+                    }
+                    Err(error) => {
+                        eprintln!("failed to enable architecture {arch}: {error}");
+                        return self.fail(Operation::EnableArchitecture, error);
+                    }
+                }
+            }
+
+            Message::Progress(event) => {
+                self.progress
+                    .get_or_insert_with(Progress::default)
+                    .update(&event);
+            }
+
             Message::AskInstallation => {
-                self.ask_install = true;
+                // Old package lists resolve dependencies to versions the mirrors no longer have.
+                if !self.cache_checked && self.config.max_cache_age_hours > 0 {
+                    self.is_cancelled = false;
+                    self.error = None;
+                    self.diagnosis = None;
+                    let max_age_hours = self.config.max_cache_age_hours;
+                    let backend = self.backend.clone();
+                    return Command::perform(
+                        async move { backend.cache_age().await },
+                        move |result| {
+                            let stale = match result {
+                                Ok(age) => cache_is_stale(age, max_age_hours),
+                                Err(why) => {
+                                    eprintln!(
+                                        "failed to read the age of the package lists: {why:#}"
+                                    );
+                                    false
+                                }
+                            };
+                            cosmic::app::Message::App(Message::CacheChecked(stale))
+                        },
+                    );
+                }
+
+                let packages = self.packages();
+                // Reinstalls and downgrades are always confirmed, as they need extra flags.
+                let needs_flags = packages
+                    .iter()
+                    .any(|package| package.action().flag().is_some());
+                if !self.config.confirm_install && !needs_flags {
+                    self.start_install();
+                    return Command::none();
+                }
+
+                self.confirm_install = true;
+                self.preview = None;
+                self.authorization = None;
+                self.is_cancelled = false;
+                self.error = None;
+                self.diagnosis = None;
+
+                let backend = self.backend.clone();
+                let simulate = command::future(async move {
+                    let result = preview::simulate(backend, packages)
+                        .await
+                        .map_err(TransactionError::from);
+                    Message::PreviewLoaded(result)
+                });
+                let backend = self.backend.clone();
+                let authorization =
+                    Command::perform(async move { backend.authorization().await }, |result| {
+                        match result {
+                            Ok(authorization) => cosmic::app::Message::App(
+                                Message::AuthorizationChecked(authorization),
+                            ),
+                            Err(why) => {
+                                eprintln!("failed to check authorization: {why:#}");
+                                cosmic::app::Message::None
+                            }
+                        }
+                    });
+                return Command::batch([simulate, authorization]);
+            }
+
+            Message::CacheChecked(stale) => {
+                self.cache_checked = true;
+                if !stale {
+                    return self.update(Message::AskInstallation);
+                }
+                self.is_refreshing = true;
+                self.allow_cancel = false;
+                self.progress = Some(Progress::default());
+            }
+
+            Message::CacheRefreshed(result) => {
+                self.is_refreshing = false;
+                self.allow_cancel = false;
+                self.progress = None;
+                match result {
+                    Ok(()) => return self.update(Message::AskInstallation),
+                    Err(error) if error.code == Error::TransactionCancelled => {
+                        self.is_cancelled = true;
+                    }
+                    // Installing again goes ahead with the old lists.
+                    Err(error) => {
+                        eprintln!("failed to refresh package lists: {error}");
+                        return self.fail(Operation::Refresh, error);
+                    }
+                }
             }
 
-            Message::PackagesInstalled(status) => {
-                self.is_installed = status;
+            Message::PreviewLoaded(result) => {
+                // The user may have cancelled while the simulation was running.
+                if self.confirm_install {
+                    match result {
+                        Ok(preview) => self.preview = Some(preview),
+                        Err(error) => {
+                            eprintln!("failed to simulate installation: {error}");
+                            self.confirm_install = false;
+                            return self.fail(Operation::Install, error);
+                        }
+                    }
+                }
+            }
+
+            Message::AuthorizationChecked(authorization) => {
+                self.authorization = Some(authorization);
+            }
+
+            Message::ConfirmInstallation => {
+                self.confirm_install = false;
+                self.preview = None;
+                self.start_install();
+            }
+
+            Message::CancelConfirmation => {
+                self.confirm_install = false;
+                self.preview = None;
+                self.cache_checked = false;
+            }
+
+            Message::AllowCancel(allow_cancel) => {
+                self.allow_cancel = allow_cancel;
+            }
+
+            Message::CancelInstallation => {
+                self.allow_cancel = false;
+                let backend = self.backend.clone();
+                return Command::perform(async move { backend.cancel().await }, |result| {
+                    if let Err(why) = result {
+                        eprintln!("failed to cancel installation: {why}");
+                    }
+                    cosmic::app::Message::None
+                });
+            }
+
+            Message::InstallationCancelled => {
+                self.is_cancelled = true;
                 self.ask_install = false;
+                self.allow_cancel = false;
                 self.progress = None;
-                if self.is_installed {
-                    self.packages = Vec::new();
-                    self.package = None;
+            }
+
+            Message::InstallationFailed(error) => {
+                eprintln!("failed to install packages: {error}");
+                self.is_installed = false;
+                self.ask_install = false;
+                self.allow_cancel = false;
+                self.progress = None;
+                return self.fail(Operation::Install, error);
+            }
+
+            Message::PackagesInstalled => {
+                self.is_installed = true;
+                self.ask_install = false;
+                self.allow_cancel = false;
+                self.progress = None;
+                self.files
+                    .retain(|file| !matches!(file.state, FileState::Loaded(_)));
+                self.update_selection();
+                self.package = None;
+                return read_status();
+            }
+
+            Message::CopyErrorDetails => {
+                if let Some(error) = &self.error {
+                    return cosmic::iced::clipboard::write(self.error_report(error));
+                }
+            }
+
+            Message::Diagnosed(diagnosis) => {
+                // The error may have been dismissed in the meantime.
+                if self.error.is_some() {
+                    self.diagnosis = Some(diagnosis);
                 }
             }
 
+            Message::EnableRepository(repository) => {
+                self.enable_repository = Some(repository);
+            }
+
+            Message::CancelEnableRepository => {
+                self.enable_repository = None;
+            }
+
+            Message::ConfirmEnableRepository => {
+                if let Some(repository) = self.enable_repository.take() {
+                    self.is_enabling = true;
+                    let backend = self.backend.clone();
+                    return command::future(async move {
+                        let result = enable_and_refresh(backend, repository.id)
+                            .await
+                            .map_err(TransactionError::from);
+                        Message::RepositoryEnabled(result)
+                    });
+                }
+            }
+
+            Message::RepositoryEnabled(result) => {
+                self.is_enabling = false;
+                match result {
+                    // Simulate again, now that the packages of the source are known.
+                    Ok(()) => {
+                        self.error = None;
+                        self.diagnosis = None;
+                        self.cache_checked = true;
+                        return self.update(Message::AskInstallation);
+                    }
+                    Err(error) => {
+                        eprintln!("failed to enable software source: {error}");
+                        return self.fail(Operation::EnableSource, error);
+                    }
+                }
+            }
+
+            Message::DismissError => {
+                self.error = None;
+                self.diagnosis = None;
+                self.enable_repository = None;
+            }
+
             Message::ShowDetails(package) => {
                 if self.package.is_some() {
-                    self.package = None
+                    self.package = None;
+                    self.update_clashes();
                 } else {
+                    self.contents = None;
+                    self.collapsed.clear();
+                    self.contents_search.clear();
+                    let path = package.path.clone();
                     self.package = Some(*package);
+                    self.update_clashes();
+                    return read_contents(path);
+                }
+            }
+
+            Message::SelectDetailsTab(tab) => {
+                self.details_tabs.activate(tab);
+            }
+
+            Message::ContentsLoaded(path, result) => {
+                if self
+                    .package
+                    .as_ref()
+                    .is_some_and(|package| package.path == path)
+                {
+                    self.contents = Some(result);
                 }
             }
+
+            Message::ToggleDirectory(path) => {
+                if !self.collapsed.remove(&path) {
+                    self.collapsed.insert(path);
+                }
+            }
+
+            Message::SearchContents(search) => {
+                self.contents_search = search;
+            }
         }
 
         Command::none()
@@ -337,14 +783,21 @@ impl Application for AppModel {
         .width(Length::FillPortion(1))
         .on_press(Message::SelectFile);
 
-        let install_btn: Option<Element<'_, _>> = if !self.packages.is_empty() {
+        let is_loading = self
+            .files
+            .iter()
+            .any(|file| matches!(file.state, FileState::Loading));
+        let install_btn: Option<Element<'_, _>> = if !self.selected.is_empty() {
             Some(
                 widget::button(
                     widget::text(fl!("install-file")).horizontal_alignment(Horizontal::Center),
                 )
                 .padding(10)
                 .width(Length::FillPortion(1))
-                .on_press(Message::AskInstallation)
+                .on_press_maybe(
+                    (!is_loading && !self.confirm_install && !self.has_incompatible())
+                        .then_some(Message::AskInstallation),
+                )
                 .style(theme::Button::Suggested)
                 .into(),
             )
@@ -364,22 +817,122 @@ impl Application for AppModel {
         .width(Length::Fill)
         .align_x(Horizontal::Center);
 
+        let selected = &self.selected;
+        let mut index = 0;
+
+        let mismatches: Vec<Element<'_, _>> = self
+            .dependencies
+            .iter()
+            .filter_map(|dependency| match dependency.provider {
+                Provider::Mismatched(provider) => Some(
+                    widget::text(fl!(
+                        "selection-mismatch",
+                        package = selected[dependency.package].id.name.as_str(),
+                        relation = dependency.relation.as_str(),
+                        provider = selected[provider].id.name.as_str(),
+                        version = selected[provider].id.version.as_str()
+                    ))
+                    .into(),
+                ),
+                Provider::Selected(_) | Provider::System => None,
+            })
+            .collect();
+        let mismatches: Option<Element<'_, _>> = (!mismatches.is_empty()).then(|| {
+            widget::container(
+                widget::container(widget::column::with_children(mismatches)).max_width(800),
+            )
+            .width(Length::Fill)
+            .align_x(Horizontal::Center)
+            .into()
+        });
+
         let mut files_column = widget::list_column();
 
-        for package in self.packages.clone() {
-            files_column = files_column.add(settings::item(
-                package.name.clone(),
-                row()
-                    .push(widget::text(package.path.clone()))
-                    .spacing(28)
-                    .push(
-                        widget::button::standard(fl!("show-details"))
-                            .on_press(Message::ShowDetails(Box::new(package))),
-                    ),
-            ));
+        for file in &self.files {
+            let remove_btn = || {
+                widget::button::standard(fl!("remove"))
+                    .on_press(Message::RemoveFile(file.path.clone()))
+            };
+
+            match &file.state {
+                FileState::Loading => {
+                    files_column = files_column.add(settings::item(
+                        file.file_name().to_string(),
+                        row()
+                            .spacing(28)
+                            .align_items(Alignment::Center)
+                            .push(widget::text(fl!("loading")))
+                            .push(remove_btn()),
+                    ));
+                }
+                FileState::Loaded(packages) => {
+                    for package in packages {
+                        let providers: Vec<&str> = self
+                            .dependencies
+                            .iter()
+                            .filter(|dependency| dependency.package == index)
+                            .filter_map(|dependency| match dependency.provider {
+                                Provider::Selected(provider) => {
+                                    Some(selected[provider].id.name.as_str())
+                                }
+                                Provider::Mismatched(_) | Provider::System => None,
+                            })
+                            .collect();
+                        index += 1;
+                        let uses = (!providers.is_empty()).then(|| {
+                            widget::text::caption(fl!(
+                                "uses-selected",
+                                packages = providers.join(", ")
+                            ))
+                        });
+
+                        files_column = files_column.add(settings::item(
+                            package.id.name.clone(),
+                            row()
+                                .push(widget::text(package.path.clone()))
+                                .spacing(28)
+                                .align_items(Alignment::Center)
+                                .push(
+                                    widget::column()
+                                        .push(widget::text::caption(action_text(package)))
+                                        .push_maybe(uses),
+                                )
+                                .push(
+                                    widget::button::standard(fl!("show-details"))
+                                        .on_press(Message::ShowDetails(Box::new(package.clone()))),
+                                )
+                                .push(remove_btn()),
+                        ));
+                        if let Some(banner) = self.architecture_banner(package) {
+                            files_column = files_column.add(banner);
+                        }
+                        if let Some(banner) = self.repository_banner(package) {
+                            files_column = files_column.add(banner);
+                        }
+                    }
+                }
+                FileState::Failed(error) => {
+                    files_column = files_column.add(settings::item(
+                        file.file_name().to_string(),
+                        row()
+                            .spacing(28)
+                            .align_items(Alignment::Center)
+                            .push(
+                                widget::column()
+                                    .push(widget::text(errors::explanation(error.code)))
+                                    .push(widget::text::caption(error.details.clone())),
+                            )
+                            .push(
+                                widget::button::standard(fl!("retry"))
+                                    .on_press(Message::RetryFile(file.path.clone())),
+                            )
+                            .push(remove_btn()),
+                    ));
+                }
+            }
         }
 
-        let files: Option<Element<'_, _>> = if !self.packages.is_empty() {
+        let files: Option<Element<'_, _>> = if !self.files.is_empty() {
             Some(
                 widget::container(widget::container(files_column).max_width(800))
                     .align_x(Horizontal::Center)
@@ -389,8 +942,23 @@ impl Application for AppModel {
             None
         };
 
+        let cancelled: Option<Element<'_, _>> = if self.is_cancelled {
+            Some(
+                widget::container(widget::text(fl!("installation-cancelled")))
+                    .width(Length::Fill)
+                    .align_x(Horizontal::Center)
+                    .into(),
+            )
+        } else {
+            None
+        };
+
         let content = widget::column()
             .spacing(16)
+            .push_maybe(cancelled)
+            .push_maybe(self.error_view())
+            .push_maybe(self.confirmation())
+            .push_maybe(mismatches)
             // .push(header)
             .push_maybe(self.progress().or(Some(header.into())))
             .push_maybe(files)
@@ -406,6 +974,58 @@ impl Application for AppModel {
 }
 
 impl AppModel {
+    /// Builds the model around the runtime core and the given configuration.
+    fn new(core: Core, config: Config, config_handler: Option<cosmic_config::Config>) -> Self {
+        Self {
+            core,
+            context_page: ContextPage::default(),
+            key_binds: HashMap::new(),
+            config,
+            config_handler,
+            backend: Arc::new(PackageKitBackend::default()),
+            loader: DetailsLoader::default(),
+
+            files: Vec::new(),
+            selected: Vec::new(),
+            dependencies: Vec::new(),
+            package: None,
+            details_tabs: segmented_button::Model::builder()
+                .insert(|tab| {
+                    tab.text(fl!("tab-details"))
+                        .data(DetailsTab::Details)
+                        .activate()
+                })
+                .insert(|tab| tab.text(fl!("tab-files")).data(DetailsTab::Files))
+                .build(),
+            contents: None,
+            collapsed: HashSet::new(),
+            contents_search: String::new(),
+            is_installed: false,
+            is_cancelled: false,
+            ask_install: false,
+            cache_checked: false,
+            is_refreshing: false,
+            cache_age_labels: CACHE_AGES
+                .iter()
+                .map(|&hours| cache_age_text(hours))
+                .collect(),
+            confirm_install: false,
+            preview: None,
+            authorization: None,
+            allow_cancel: false,
+            progress: None,
+            error: None,
+            failed: Operation::Install,
+            diagnosis: None,
+            status: None,
+            clashes: Vec::new(),
+            architectures: None,
+            enabling_architecture: None,
+            enable_repository: None,
+            is_enabling: false,
+        }
+    }
+
     /// The about page for this app.
     pub fn about(&self) -> Element<Message> {
         let cosmic_theme::Spacing { space_xxs, .. } = theme::active().cosmic().spacing;
@@ -427,6 +1047,152 @@ impl AppModel {
             .into()
     }
 
+    /// The packages of every loaded file.
+    fn packages(&self) -> Vec<Package> {
+        self.files
+            .iter()
+            .flat_map(|file| file.packages().iter().cloned())
+            .collect()
+    }
+
+    /// Collects the selected packages and works out how they depend on each other.
+    fn update_selection(&mut self) {
+        self.selected = self.packages();
+        self.dependencies = selection::dependencies(&self.selected);
+    }
+
+    /// Works out the clashes of the package in the details view, once dpkg's status is read.
+    fn update_clashes(&mut self) {
+        self.clashes = match (&self.package, &self.status) {
+            (Some(package), Some(status)) => conflict::analyze(package, status),
+            _ => Vec::new(),
+        };
+    }
+
+    /// Starts installing the loaded packages; the install subscription does the work.
+    ///
+    /// The next install checks the age of the package lists again.
+    fn start_install(&mut self) {
+        self.cache_checked = false;
+        self.ask_install = true;
+        self.is_cancelled = false;
+        self.error = None;
+        self.diagnosis = None;
+        self.progress = Some(Progress::default());
+    }
+
+    /// How well the system supports a package's architecture; unknown counts as native.
+    fn support(&self, package: &Package) -> Support {
+        self.architectures
+            .as_ref()
+            .map_or(Support::Native, |architectures| {
+                architectures.support(&package.id.arch)
+            })
+    }
+
+    /// Whether a selected package is for an architecture that cannot be installed yet.
+    fn has_incompatible(&self) -> bool {
+        self.selected.iter().any(|package| {
+            matches!(
+                self.support(package),
+                Support::NotEnabled | Support::Unsupported
+            )
+        })
+    }
+
+    /// Explains why a package's architecture cannot be installed, offering to enable it.
+    fn architecture_banner(&self, package: &Package) -> Option<Element<Message>> {
+        let native = self.architectures.as_ref()?.native.as_str();
+        let arch = package.id.arch.as_str();
+
+        match self.support(package) {
+            Support::Native | Support::Enabled => None,
+            Support::NotEnabled => {
+                let action: Element<_> = if self.enabling_architecture.as_deref() == Some(arch) {
+                    widget::text(fl!("enabling-arch", arch = arch)).into()
+                } else {
+                    let busy = self.enabling_architecture.is_some()
+                        || self.confirm_install
+                        || self.ask_install
+                        || self.is_refreshing;
+                    widget::button::suggested(fl!("enable-arch", arch = arch))
+                        .on_press_maybe(
+                            (!busy).then(|| Message::EnableArchitecture(arch.to_string())),
+                        )
+                        .into()
+                };
+                Some(
+                    row()
+                        .spacing(16)
+                        .align_items(Alignment::Center)
+                        .push(
+                            widget::text(fl!("arch-not-enabled", arch = arch, native = native))
+                                .width(Length::Fill),
+                        )
+                        .push(action)
+                        .into(),
+                )
+            }
+            Support::Unsupported => {
+                Some(widget::text(fl!("arch-unsupported", arch = arch, native = native)).into())
+            }
+        }
+    }
+
+    /// Offers to install a package from the repository that has the same or a newer version.
+    fn repository_banner(&self, package: &Package) -> Option<Element<Message>> {
+        let available = package.available.as_ref()?;
+        let busy = self.confirm_install || self.ask_install || self.is_refreshing;
+
+        let (text, button) = if package.use_repository {
+            (
+                fl!(
+                    "repository-chosen",
+                    version = available.version.as_str(),
+                    origin = available.data.as_str()
+                ),
+                widget::button::standard(fl!("use-file")),
+            )
+        } else {
+            (
+                fl!(
+                    "repository-available",
+                    version = available.version.as_str(),
+                    origin = available.data.as_str()
+                ),
+                widget::button::suggested(fl!("use-repository")),
+            )
+        };
+        let message = Message::UseRepository(
+            package.path.clone(),
+            package.id.clone(),
+            !package.use_repository,
+        );
+
+        Some(
+            row()
+                .spacing(16)
+                .align_items(Alignment::Center)
+                .push(widget::text(text).width(Length::Fill))
+                .push(button.on_press_maybe((!busy).then_some(message)))
+                .into(),
+        )
+    }
+
+    /// Reads the details of a file in the background.
+    fn load(&self, path: String) -> Command<Message> {
+        let backend = self.backend.clone();
+        let loader = self.loader.clone();
+        let status = self.status.clone();
+        command::future(async move {
+            let result = loader
+                .load(backend, path.clone(), status)
+                .await
+                .map_err(TransactionError::from);
+            Message::PackagesLoaded(path, result)
+        })
+    }
+
     /// Updates the header and window titles.
     pub fn update_title(&mut self) -> Command<Message> {
         let window_title = fl!("app-title");
@@ -436,15 +1202,18 @@ impl AppModel {
     pub fn details(&self) -> Option<Element<Message>> {
         self.package.clone().map(|package| {
             let column = widget::list_column()
-                .add(settings::item(fl!("id"), widget::text(package.id)))
-                .add(settings::item(fl!("name"), widget::text(package.name)))
+                .add(settings::item(
+                    fl!("id"),
+                    widget::text(package.id.to_string()),
+                ))
+                .add(settings::item(fl!("name"), widget::text(package.id.name)))
                 .add(settings::item(
                     fl!("version"),
-                    widget::text(package.version),
+                    widget::text(package.id.version),
                 ))
                 .add(settings::item(
                     fl!("architecture"),
-                    widget::text(package.architecture),
+                    widget::text(package.id.arch),
                 ))
                 .add(settings::item(
                     fl!("summary"),
@@ -459,17 +1228,552 @@ impl AppModel {
                     fl!("license"),
                     widget::text(package.license),
                 ))
-                .add(settings::item(fl!("size"), widget::text(package.size)));
+                .add(settings::item(
+                    fl!("size"),
+                    widget::text(format_size(package.size)),
+                ));
+
+            // Fields only known from the control file, shown when the package sets them.
+            let control_fields = [
+                (fl!("maintainer"), package.maintainer),
+                (fl!("section"), package.section),
+                (fl!("priority"), package.priority),
+                (
+                    fl!("installed-size"),
+                    package.installed_size.map(format_size).unwrap_or_default(),
+                ),
+                (fl!("homepage"), package.homepage),
+                (fl!("multi-arch"), package.multi_arch),
+                (fl!("pre-depends"), package.pre_depends),
+                (fl!("depends"), package.depends),
+                (fl!("recommends"), package.recommends),
+                (fl!("conflicts"), package.conflicts),
+                (fl!("breaks"), package.breaks),
+                (fl!("replaces"), package.replaces),
+                (fl!("provides"), package.provides),
+            ];
+            let column = control_fields
+                .into_iter()
+                .filter(|(_, value)| !value.is_empty())
+                .fold(column, |column, (title, value)| {
+                    column.add(settings::item(title, widget::text(value)))
+                });
+
+            // Dependencies that none of the other selected files satisfy.
+            let from_system: Vec<&str> = self
+                .selected
+                .iter()
+                .position(|other| other.path == package.path && other.id == package.id)
+                .map(|index| {
+                    self.dependencies
+                        .iter()
+                        .filter(|dependency| {
+                            dependency.package == index && dependency.provider == Provider::System
+                        })
+                        .map(|dependency| dependency.relation.as_str())
+                        .collect()
+                })
+                .unwrap_or_default();
+            let column = if from_system.is_empty() {
+                column
+            } else {
+                column.add(settings::item(
+                    fl!("from-system"),
+                    widget::text(from_system.join(", ")),
+                ))
+            };
+
+            let column = self.clashes.iter().fold(column, |column, clash| {
+                column.add(settings::item(
+                    level_text(clash.level()),
+                    widget::text(clash_text(clash)),
+                ))
+            });
+
+            let tab = match self.details_tabs.active_data::<DetailsTab>() {
+                Some(DetailsTab::Files) => self.contents_view(),
+                _ => column.into(),
+            };
+            let tabs = widget::segmented_control::horizontal(&self.details_tabs)
+                .on_activate(Message::SelectDetailsTab);
+
+            widget::container(
+                widget::container(widget::column().spacing(16).push(tabs).push(tab)).max_width(800),
+            )
+            .align_x(Horizontal::Center)
+            .into()
+        })
+    }
+
+    /// The files tab of the details view: the package's files as a tree, or the search
+    /// results, marking what would overwrite files already on the system.
+    fn contents_view(&self) -> Element<Message> {
+        let contents = match &self.contents {
+            None => return widget::text(fl!("loading")).into(),
+            Some(Err(error)) => {
+                return widget::column()
+                    .push(widget::text(fl!("contents-failed")))
+                    .push(widget::text::caption(error.details.clone()))
+                    .into()
+            }
+            Some(Ok(contents)) => contents,
+        };
+
+        let searching = !self.contents_search.trim().is_empty();
+        let search = widget::search_input(fl!("search-files"), &self.contents_search)
+            .on_input(Message::SearchContents)
+            .on_clear(Message::SearchContents(String::new()));
+
+        let mut summary = fl!("file-count", files = contents.files());
+        if contents.existing() > 0 {
+            summary = format!(
+                "{summary} · {}",
+                fl!("files-existing", existing = contents.existing())
+            );
+        }
+
+        let list = contents
+            .visible(&self.collapsed, &self.contents_search)
+            .into_iter()
+            .fold(widget::list_column(), |list, entry| {
+                list.add(self.entry_row(entry, searching))
+            });
+
+        widget::column()
+            .spacing(8)
+            .push(search)
+            .push(widget::text::caption(summary))
+            .push(widget::scrollable(list).height(Length::Fixed(480.0)))
+            .into()
+    }
+
+    /// A row of the files tab, indented by depth unless it is a search result.
+    fn entry_row(&self, entry: &Entry, searching: bool) -> Element<Message> {
+        let name = if searching {
+            entry.path.as_str()
+        } else {
+            entry.name()
+        };
+        let name = match &entry.kind {
+            EntryKind::Symlink(target) | EntryKind::Hardlink(target) => {
+                fl!("link", name = name, target = target.as_str())
+            }
+            EntryKind::Directory | EntryKind::File | EntryKind::Other => name.to_string(),
+        };
+        let name = if entry.exists {
+            widget::text(name).style(theme::Text::Accent)
+        } else {
+            widget::text(name)
+        };
+
+        let toggle: Element<_> = if entry.is_directory() && !searching {
+            let icon = if self.collapsed.contains(&entry.path) {
+                "go-next-symbolic"
+            } else {
+                "go-down-symbolic"
+            };
+            widget::button::icon(widget::icon::from_name(icon))
+                .on_press(Message::ToggleDirectory(entry.path.clone()))
+                .into()
+        } else {
+            widget::container(widget::text(""))
+                .width(Length::Fixed(32.0))
+                .into()
+        };
+        let indent = if searching {
+            0
+        } else {
+            entry.depth() as u16 * 16
+        };
+
+        widget::container(
+            row()
+                .spacing(8)
+                .align_items(Alignment::Center)
+                .push(toggle)
+                .push(name.width(Length::Fill))
+                .push_maybe(
+                    entry
+                        .exists
+                        .then(|| widget::text::caption(fl!("file-exists"))),
+                )
+                .push_maybe(
+                    entry
+                        .is_setuid()
+                        .then(|| widget::text::caption(fl!("setuid"))),
+                )
+                .push(widget::text::caption(entry.permissions()))
+                .push(widget::text::caption(format_size(entry.size))),
+        )
+        .padding([0, 0, 0, indent])
+        .into()
+    }
+
+    pub fn error_view(&self) -> Option<Element<Message>> {
+        self.error.as_ref().map(|error| {
+            let cosmic_theme::Spacing {
+                space_xxs, space_s, ..
+            } = theme::active().cosmic().spacing;
+
+            let actions = widget::row()
+                .spacing(space_s)
+                .push(
+                    widget::button::standard(fl!("copy-details"))
+                        .on_press(Message::CopyErrorDetails),
+                )
+                .push(widget::button::standard(fl!("dismiss")).on_press(Message::DismissError));
+
+            let column = widget::column()
+                .spacing(space_xxs)
+                .push(widget::text::title4(self.failed.failed_text()))
+                .push(widget::text::heading(errors::explanation(error.code)))
+                .push(widget::text(errors::suggestion(error.code)))
+                .push(widget::text::caption(fl!(
+                    "error-code",
+                    code = error.code.as_str()
+                )))
+                .push(widget::text::monotext(error.details.clone()))
+                .push_maybe(self.diagnosis_view())
+                .push(actions);
 
             widget::container(widget::container(column).max_width(800))
+                .width(Length::Fill)
                 .align_x(Horizontal::Center)
                 .into()
         })
     }
+
+    /// The missing dependencies, and the sources and files that could provide them.
+    fn diagnosis_view(&self) -> Option<Element<Message>> {
+        let diagnosis = self.diagnosis.as_ref().filter(|d| !d.unmet.is_empty())?;
+
+        let unmet = diagnosis
+            .unmet
+            .iter()
+            .fold(widget::list_column(), |list, unmet| {
+                let needed_by = if unmet.reason.is_empty() {
+                    fl!("needed-by", package = unmet.package.as_str())
+                } else {
+                    fl!(
+                        "needed-by-reason",
+                        package = unmet.package.as_str(),
+                        reason = unmet.reason.as_str()
+                    )
+                };
+                list.add(settings::item(unmet.relation(), widget::text(needed_by)))
+            });
+        let mut column = widget::column()
+            .push(widget::text::heading(fl!("missing-dependencies")))
+            .push(unmet);
+
+        if !diagnosis.disabled.is_empty() {
+            let sources =
+                diagnosis
+                    .disabled
+                    .iter()
+                    .fold(widget::list_column(), |list, repository| {
+                        list.add(settings::item(
+                            repository.description.clone(),
+                            row()
+                                .spacing(28)
+                                .align_items(Alignment::Center)
+                                .push(widget::text::caption(repository.id.clone()))
+                                .push(
+                                    widget::button::standard(fl!("enable"))
+                                        .on_press_maybe((!self.is_enabling).then(|| {
+                                            Message::EnableRepository(repository.clone())
+                                        })),
+                                ),
+                        ))
+                    });
+            column = column
+                .push(widget::text::heading(fl!("disabled-sources")))
+                .push(widget::text::caption(fl!("disabled-sources-unverified")))
+                .push(sources);
+        }
+
+        if let Some(repository) = &self.enable_repository {
+            column = column
+                .push(widget::text(fl!(
+                    "enable-source-question",
+                    description = repository.description.as_str(),
+                    id = repository.id.as_str()
+                )))
+                .push(
+                    widget::row()
+                        .spacing(16)
+                        .push(
+                            widget::button::standard(fl!("cancel"))
+                                .on_press(Message::CancelEnableRepository),
+                        )
+                        .push(
+                            widget::button::suggested(fl!("enable"))
+                                .on_press(Message::ConfirmEnableRepository),
+                        ),
+                );
+        } else if self.is_enabling {
+            column = column.push(widget::text(fl!("enabling-source")));
+        }
+
+        // Files added since the diagnosis are no longer offered.
+        let candidates: Vec<_> = diagnosis
+            .candidates
+            .iter()
+            .filter(|candidate| self.files.iter().all(|file| file.path != candidate.path))
+            .collect();
+        if !candidates.is_empty() {
+            let files = candidates
+                .into_iter()
+                .fold(widget::list_column(), |list, candidate| {
+                    list.add(settings::item(
+                        format!("{} {}", candidate.name, candidate.version),
+                        row()
+                            .spacing(28)
+                            .align_items(Alignment::Center)
+                            .push(widget::text::caption(candidate.path.clone()))
+                            .push(
+                                widget::button::standard(fl!("add"))
+                                    .on_press(Message::UpdatePackages(candidate.path.clone())),
+                            ),
+                    ))
+                });
+            column = column
+                .push(widget::text::heading(fl!("installers-nearby")))
+                .push(files);
+        }
+
+        Some(column.into())
+    }
+
+    /// Shows why an operation failed, diagnosing unmet dependencies in the background.
+    fn fail(&mut self, operation: Operation, error: TransactionError) -> Command<Message> {
+        let diagnose = error.code == Error::DepResolutionFailed;
+        let details = error.details.clone();
+        self.error = Some(error);
+        self.failed = operation;
+        self.diagnosis = None;
+        if !diagnose {
+            return Command::none();
+        }
+
+        let backend = self.backend.clone();
+        let selected = self.files.iter().map(|file| file.path.clone()).collect();
+        command::future(async move {
+            Message::Diagnosed(unmet::diagnose(backend, details, selected).await)
+        })
+    }
+
+    /// The text copied by "Copy details", meant to be pasted into a bug report.
+    fn error_report(&self, error: &TransactionError) -> String {
+        let mut report = format!("error code: {}\n{}\n", error.code, error.details);
+        let packages = self.packages();
+        if !packages.is_empty() {
+            report.push_str("\npackages:\n");
+            for package in &packages {
+                report.push_str(&format!("  {} ({})\n", package.id, package.path));
+            }
+        }
+        report
+    }
+
+    /// Summarizes what the install will change and asks the user to go ahead.
+    pub fn confirmation(&self) -> Option<Element<Message>> {
+        if !self.confirm_install {
+            return None;
+        }
+        let cosmic_theme::Spacing {
+            space_xxs, space_s, ..
+        } = theme::active().cosmic().spacing;
+
+        let packages = &self.selected;
+        let selected = packages
+            .iter()
+            .fold(widget::list_column(), |list, package| {
+                list.add(settings::item(
+                    package.id.name.clone(),
+                    widget::text(action_text(package)),
+                ))
+            });
+
+        let mut column = widget::column()
+            .spacing(space_xxs)
+            .push(widget::text::title4(fl!("confirm-title")))
+            .push(selected);
+
+        match &self.preview {
+            None => column = column.push(widget::text(fl!("simulating"))),
+            Some(preview) if preview.is_empty() => {
+                column = column.push(widget::text(fl!("no-other-changes")));
+            }
+            Some(preview) => {
+                // The selected packages are listed above with their action.
+                let chosen: Vec<&PackageId> = packages
+                    .iter()
+                    .filter_map(Package::repository_choice)
+                    .collect();
+                let groups = [
+                    (fl!("will-install"), &preview.install),
+                    (fl!("will-upgrade"), &preview.upgrade),
+                    (fl!("will-downgrade"), &preview.downgrade),
+                    (fl!("will-remove"), &preview.remove),
+                ];
+                for (title, packages) in groups {
+                    let packages: Vec<&PreviewPackage> = packages
+                        .iter()
+                        .filter(|package| {
+                            package.origin() != "local" && !chosen.contains(&&package.id)
+                        })
+                        .collect();
+                    if packages.is_empty() {
+                        continue;
+                    }
+                    let list = packages
+                        .into_iter()
+                        .fold(widget::list_column(), |list, package| {
+                            list.add(settings::item(
+                                package.id.name.clone(),
+                                widget::text(preview_text(package)),
+                            ))
+                        });
+                    column = column.push(widget::text::heading(title)).push(list);
+                }
+            }
+        }
+
+        let mut sizes = Vec::new();
+        let disk_usage: u64 = packages
+            .iter()
+            .filter_map(|package| package.installed_size)
+            .sum();
+        if disk_usage > 0 {
+            sizes.push(fl!("disk-usage", size = format_size(disk_usage)));
+        }
+        if let Some(preview) = self.preview.as_ref().filter(|p| p.download_size > 0) {
+            sizes.push(fl!(
+                "download-size",
+                size = format_size(preview.download_size)
+            ));
+        }
+        if !sizes.is_empty() {
+            column = column.push(widget::text::caption(sizes.join(" · ")));
+        }
+
+        if let Some(authorization) = self.authorization {
+            column = column.push(widget::text::caption(match authorization {
+                Authorization::Authorized => fl!("auth-not-required"),
+                Authorization::Challenge => fl!("auth-required"),
+                Authorization::Denied => fl!("auth-denied"),
+            }));
+        }
+
+        let can_install =
+            self.preview.is_some() && self.authorization != Some(Authorization::Denied);
+        let actions = widget::row()
+            .spacing(space_s)
+            .push(widget::button::standard(fl!("cancel")).on_press(Message::CancelConfirmation))
+            .push(
+                widget::button::suggested(fl!("install-file"))
+                    .on_press_maybe(can_install.then_some(Message::ConfirmInstallation)),
+            );
+
+        Some(
+            widget::container(widget::container(column.push(actions)).max_width(800))
+                .width(Length::Fill)
+                .align_x(Horizontal::Center)
+                .into(),
+        )
+    }
+
+    /// The settings page for this app.
+    pub fn settings(&self) -> Element<Message> {
+        widget::settings::view_column(vec![settings::section()
+            .add(settings::item(
+                fl!("confirm-install"),
+                widget::toggler(
+                    None,
+                    self.config.confirm_install,
+                    Message::SetConfirmInstall,
+                ),
+            ))
+            .add(settings::item(
+                fl!("refresh-lists"),
+                widget::dropdown(
+                    &self.cache_age_labels,
+                    CACHE_AGES
+                        .iter()
+                        .position(|&hours| hours == self.config.max_cache_age_hours),
+                    |index| Message::SetMaxCacheAge(CACHE_AGES[index]),
+                ),
+            ))
+            .into()])
+        .into()
+    }
+
     pub fn progress(&self) -> Option<Element<Message>> {
-        self.progress.map(|progress| {
+        self.progress.as_ref().map(|progress| {
+            let cosmic_theme::Spacing {
+                space_xxs, space_s, ..
+            } = theme::active().cosmic().spacing;
+
+            let cancel_btn = widget::button::standard(fl!("cancel"))
+                .on_press_maybe(self.allow_cancel.then_some(Message::CancelInstallation));
+
+            let mut times = Vec::new();
+            if progress.speed > 0 {
+                times.push(format_speed(progress.speed));
+            }
+            if progress.remaining_time > 0 {
+                times.push(fl!(
+                    "time-remaining",
+                    time = format_duration(progress.remaining_time)
+                ));
+            }
+            times.push(fl!(
+                "time-elapsed",
+                time = format_duration(progress.elapsed_time)
+            ));
+
+            let overall = widget::column()
+                .spacing(space_xxs)
+                .push(widget::text::heading(status_text(progress.status)))
+                .push(
+                    widget::row()
+                        .spacing(16)
+                        .align_items(Alignment::Center)
+                        .push(ProgressBar::new(
+                            0.0..=100.0,
+                            progress.percentage.unwrap_or(0) as f32,
+                        ))
+                        .push(cancel_btn),
+                )
+                .push(widget::text::caption(times.join(" · ")));
+
+            let items = (!progress.items.is_empty()).then(|| {
+                let mut items = widget::list_column();
+                for item in &progress.items {
+                    items = items.add(settings::item(
+                        item.name().to_string(),
+                        row()
+                            .spacing(16)
+                            .align_items(Alignment::Center)
+                            .push(widget::text(status_text(item.status)))
+                            .push(
+                                ProgressBar::new(0.0..=100.0, item.percentage.unwrap_or(0) as f32)
+                                    .width(Length::Fixed(200.0)),
+                            ),
+                    ));
+                }
+                items
+            });
+
             widget::container(
-                widget::container(ProgressBar::new(0.0..=100.0, progress)).max_width(800),
+                widget::container(
+                    widget::column()
+                        .spacing(space_s)
+                        .push(overall)
+                        .push_maybe(items),
+                )
+                .max_width(800),
             )
             .align_x(Horizontal::Center)
             .into()
@@ -477,24 +1781,327 @@ impl AppModel {
     }
 }
 
+/// What installing a package does, including the version it replaces.
+fn action_text(package: &Package) -> String {
+    if let Some(available) = package.repository_choice() {
+        return fl!(
+            "action-from-repository",
+            version = available.version.as_str(),
+            origin = available.data.as_str()
+        );
+    }
+    let installed = package
+        .installed
+        .as_ref()
+        .map(|installed| installed.version.as_str())
+        .unwrap_or_default();
+    match package.action() {
+        Action::Install => fl!("action-install"),
+        Action::Upgrade => fl!("action-upgrade", installed = installed),
+        Action::Reinstall => fl!("action-reinstall"),
+        Action::Downgrade => fl!("action-downgrade", installed = installed),
+    }
+}
+
+/// How worrying a clash with an installed package is.
+fn level_text(level: Level) -> String {
+    match level {
+        Level::Notice => fl!("level-notice"),
+        Level::Warning => fl!("level-warning"),
+        Level::Danger => fl!("level-danger"),
+    }
+}
+
+/// What a clash does to the installed package, and the relation behind it.
+fn clash_text(clash: &Clash) -> String {
+    let package = clash.installed.name.as_str();
+    let version = clash.installed.version.as_str();
+    let relation = clash.relation.as_str();
+    match (clash.kind, clash.reverse) {
+        (Kind::Conflicts, false) => fl!(
+            "clash-conflicts",
+            package = package,
+            version = version,
+            relation = relation
+        ),
+        (Kind::Conflicts, true) => fl!(
+            "clash-conflicted-by",
+            package = package,
+            version = version,
+            relation = relation
+        ),
+        (Kind::Breaks, false) => fl!(
+            "clash-breaks",
+            package = package,
+            version = version,
+            relation = relation
+        ),
+        (Kind::Breaks, true) => fl!(
+            "clash-broken-by",
+            package = package,
+            version = version,
+            relation = relation
+        ),
+        (Kind::Replaces, _) => fl!(
+            "clash-replaces",
+            package = package,
+            version = version,
+            relation = relation
+        ),
+    }
+}
+
+/// Lists the files of the package at `path` in the background.
+fn read_contents(path: String) -> Command<Message> {
+    let task_path = path.clone();
+    Command::perform(
+        tokio::task::spawn_blocking(move || Contents::read(task_path)),
+        move |result| {
+            let result = result
+                .map_err(anyhow::Error::from)
+                .and_then(|contents| contents)
+                .map(Arc::new)
+                .map_err(|why| {
+                    eprintln!("failed to list package files: {why:#}");
+                    TransactionError::from(why)
+                });
+            cosmic::app::Message::App(Message::ContentsLoaded(path, result))
+        },
+    )
+}
+
+/// Reads dpkg's status file in the background.
+fn read_status() -> Command<Message> {
+    Command::perform(
+        tokio::task::spawn_blocking(|| Database::read(dpkg::STATUS)),
+        |result| match result
+            .map_err(anyhow::Error::from)
+            .and_then(|status| status)
+        {
+            Ok(status) => cosmic::app::Message::App(Message::StatusRead(Arc::new(status))),
+            Err(why) => {
+                eprintln!("failed to read dpkg status: {why:#}");
+                cosmic::app::Message::None
+            }
+        },
+    )
+}
+
+/// The version of a package in the preview, where it comes from, and what it replaces.
+fn preview_text(package: &PreviewPackage) -> String {
+    match &package.installed_version {
+        Some(installed) => fl!(
+            "preview-replaces",
+            installed = installed.as_str(),
+            version = package.id.version.as_str(),
+            origin = package.origin()
+        ),
+        None => fl!(
+            "preview-version",
+            version = package.id.version.as_str(),
+            origin = package.origin()
+        ),
+    }
+}
+
+/// The choices for how old, in hours, the package lists may be before installing.
+const CACHE_AGES: [u32; 5] = [0, 1, 6, 24, 24 * 7];
+
+/// Describes a choice of [`CACHE_AGES`].
+fn cache_age_text(hours: u32) -> String {
+    match hours {
+        0 => fl!("refresh-never"),
+        hours if hours % 24 == 0 => fl!("refresh-after-days", days = hours / 24),
+        hours => fl!("refresh-after-hours", hours = hours),
+    }
+}
+
+/// Whether package lists last refreshed `age` ago are older than the threshold.
+fn cache_is_stale(age: Option<Duration>, max_age_hours: u32) -> bool {
+    let max_age = Duration::from_secs(u64::from(max_age_hours) * 60 * 60);
+    !age.is_some_and(|age| age <= max_age)
+}
+
+/// Refreshes the package lists, reporting progress and the outcome as messages.
+async fn refresh(backend: Arc<dyn PackageBackend>, msg_tx: &mut mpsc::Sender<Message>) {
+    let result = match backend.refresh_cache().await {
+        Ok(mut events) => loop {
+            match events.next().await {
+                Some(Ok(event)) if is_progress(&event) => {
+                    let _ = msg_tx.send(Message::Progress(event)).await;
+                }
+                Some(Ok(TransactionEvent::AllowCancel(allow_cancel))) => {
+                    let _ = msg_tx.send(Message::AllowCancel(allow_cancel)).await;
+                }
+                Some(Ok(TransactionEvent::ErrorCode { code, details })) => {
+                    break Err(TransactionError { code, details });
+                }
+                Some(Ok(TransactionEvent::Finished { exit, .. })) => {
+                    break match exit {
+                        Exit::Success => Ok(()),
+                        Exit::Cancelled => Err(TransactionError {
+                            code: Error::TransactionCancelled,
+                            details: String::new(),
+                        }),
+                        exit => Err(TransactionError {
+                            code: Error::Unknown,
+                            details: format!("the transaction finished with {exit}"),
+                        }),
+                    };
+                }
+                Some(Ok(_)) => (),
+                Some(Err(why)) => break Err(why.into()),
+                None => {
+                    break Err(TransactionError {
+                        code: Error::Unknown,
+                        details: String::from("the transaction ended without finishing"),
+                    })
+                }
+            }
+        },
+        Err(why) => Err(why.into()),
+    };
+
+    let _ = msg_tx.send(Message::CacheRefreshed(result)).await;
+}
+
+/// Whether the event updates the progress panel.
+fn is_progress(event: &TransactionEvent) -> bool {
+    matches!(
+        event,
+        TransactionEvent::Status(_)
+            | TransactionEvent::Percentage(_)
+            | TransactionEvent::ItemProgress { .. }
+            | TransactionEvent::Speed(_)
+            | TransactionEvent::ElapsedTime(_)
+            | TransactionEvent::RemainingTime(_)
+    )
+}
+
+/// Adds a foreign architecture to dpkg, then refreshes the package lists to know its packages.
+async fn enable_architecture(backend: Arc<dyn PackageBackend>, arch: String) -> anyhow::Result<()> {
+    arch::enable(&arch).await?;
+    finish(backend.refresh_cache().await?).await
+}
+
+/// Enables a software source, then refreshes the package lists so its packages are known.
+async fn enable_and_refresh(
+    backend: Arc<dyn PackageBackend>,
+    repo_id: String,
+) -> anyhow::Result<()> {
+    backend.enable_repository(repo_id).await?;
+    finish(backend.refresh_cache().await?).await
+}
+
+/// Installs the packages, reporting progress and the outcome as messages.
+async fn install(
+    backend: Arc<dyn PackageBackend>,
+    packages: Vec<Package>,
+    msg_tx: &mut mpsc::Sender<Message>,
+) {
+    let message = match install_packages_local(backend, packages).await {
+        Ok(mut events) => {
+            let mut outcome = None;
+            while let Some(event) = events.next().await {
+                match event {
+                    Ok(event) if is_progress(&event) => {
+                        let _ = msg_tx.send(Message::Progress(event)).await;
+                    }
+                    Ok(TransactionEvent::AllowCancel(allow_cancel)) => {
+                        let _ = msg_tx.send(Message::AllowCancel(allow_cancel)).await;
+                    }
+                    Ok(TransactionEvent::ErrorCode {
+                        code: Error::TransactionCancelled,
+                        ..
+                    }) => {
+                        outcome = Some(Message::InstallationCancelled);
+                        break;
+                    }
+                    Ok(TransactionEvent::ErrorCode { code, details }) => {
+                        outcome = Some(Message::InstallationFailed(TransactionError {
+                            code,
+                            details,
+                        }));
+                        break;
+                    }
+                    Ok(TransactionEvent::Finished { exit, .. }) => {
+                        outcome = Some(match exit {
+                            Exit::Success => Message::PackagesInstalled,
+                            Exit::Cancelled => Message::InstallationCancelled,
+                            exit => Message::InstallationFailed(TransactionError {
+                                code: Error::Unknown,
+                                details: format!("the transaction finished with {exit}"),
+                            }),
+                        });
+                    }
+                    Ok(_) => (),
+                    Err(why) => {
+                        outcome = Some(Message::InstallationFailed(why.into()));
+                        break;
+                    }
+                }
+            }
+            outcome.unwrap_or_else(|| {
+                Message::InstallationFailed(TransactionError {
+                    code: Error::Unknown,
+                    details: String::from("the transaction ended without finishing"),
+                })
+            })
+        }
+        Err(why) => Message::InstallationFailed(why.into()),
+    };
+
+    let _ = msg_tx.send(message).await;
+}
+
 /// The context page to display in the context drawer.
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
 pub enum ContextPage {
     #[default]
     About,
+    Settings,
 }
 
 impl ContextPage {
     fn title(&self) -> String {
         match self {
             Self::About => fl!("about"),
+            Self::Settings => fl!("settings"),
+        }
+    }
+}
+
+/// What the app was doing when an error happened.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Operation {
+    Install,
+    Refresh,
+    EnableArchitecture,
+    EnableSource,
+}
+
+impl Operation {
+    fn failed_text(self) -> String {
+        match self {
+            Self::Install => fl!("installation-failed"),
+            Self::Refresh => fl!("refresh-failed"),
+            Self::EnableArchitecture => fl!("enable-arch-failed"),
+            Self::EnableSource => fl!("enable-source-failed"),
         }
     }
 }
 
+/// The tabs of the details view.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DetailsTab {
+    Details,
+    Files,
+}
+
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum MenuAction {
     About,
+    Settings,
 }
 
 impl menu::action::MenuAction for MenuAction {
@@ -503,6 +2110,536 @@ impl menu::action::MenuAction for MenuAction {
     fn message(&self) -> Self::Message {
         match self {
             MenuAction::About => Message::ToggleContextPage(ContextPage::About),
+            MenuAction::Settings => Message::ToggleContextPage(ContextPage::Settings),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mock::MockBackend;
+    use crate::packagekit::{Info, Status, TransactionDetails, TransactionFlag};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn hello() -> TransactionDetails {
+        TransactionDetails {
+            package_id: String::from("hello;2.10-3;amd64;local"),
+            summary: String::from("example package based on GNU hello"),
+            description: String::new(),
+            url: String::from("https://www.gnu.org/software/hello/"),
+            license: String::from("GPL-3.0+"),
+            size: 57_348,
+            download_size: 0,
+        }
+    }
+
+    /// Adds the file at `path` to the model and loads it, like the `load` command would.
+    async fn add_file(app: &mut AppModel, path: &str) {
+        let _ = app.update(Message::UpdatePackages(path.to_string()));
+        let result = app
+            .loader
+            .load(app.backend.clone(), path.to_string(), app.status.clone())
+            .await
+            .map_err(TransactionError::from);
+        let _ = app.update(Message::PackagesLoaded(path.to_string(), result));
+    }
+
+    /// A path in the temporary directory that no other test uses.
+    fn temp_path(name: &str) -> String {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let id = NEXT.fetch_add(1, Ordering::Relaxed);
+        std::env::temp_dir()
+            .join(format!("wizard-app-{}-{id}-{name}", std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// Builds the model around `backend`, with the default configuration and a package file
+    /// selected. The file is removed once it is loaded.
+    async fn app_with(backend: MockBackend) -> AppModel {
+        let path = temp_path("hello_2.10-3_amd64.deb");
+        std::fs::write(&path, b"!<arch>\n").unwrap();
+        let mut app = AppModel::new(Core::default(), Config::default(), None);
+        app.backend = Arc::new(backend.with_details(&path, vec![hello()]));
+        add_file(&mut app, &path).await;
+        std::fs::remove_file(&path).unwrap();
+        app
+    }
+
+    /// The path of the file [`app_with`] selected.
+    fn path(app: &AppModel) -> String {
+        app.files[0].path.clone()
+    }
+
+    /// Answers the check of the package lists' age, like its command would.
+    async fn check_cache(app: &mut AppModel) {
+        let age = app.backend.cache_age().await.unwrap();
+        let stale = cache_is_stale(age, app.config.max_cache_age_hours);
+        let _ = app.update(Message::CacheChecked(stale));
+    }
+
+    /// Asks for the install and loads its preview, like the simulation command would.
+    async fn ask_installation(app: &mut AppModel) {
+        let _ = app.update(Message::AskInstallation);
+        if !app.cache_checked {
+            check_cache(app).await;
         }
+        assert!(app.confirm_install);
+        assert!(!app.ask_install);
+
+        let result = preview::simulate(app.backend.clone(), app.packages())
+            .await
+            .map_err(TransactionError::from);
+        let _ = app.update(Message::PreviewLoaded(result));
+    }
+
+    /// Runs the install subscription to completion, feeding its messages to the model.
+    async fn run_install(app: &mut AppModel) -> Vec<Message> {
+        ask_installation(app).await;
+        let _ = app.update(Message::ConfirmInstallation);
+        assert!(app.ask_install);
+
+        let (mut msg_tx, msg_rx) = mpsc::channel(64);
+        install(app.backend.clone(), app.packages(), &mut msg_tx).await;
+        drop(msg_tx);
+
+        let messages: Vec<Message> = msg_rx.collect().await;
+        for message in messages.clone() {
+            let _ = app.update(message);
+        }
+        messages
+    }
+
+    #[tokio::test]
+    async fn install_reports_progress_and_clears_packages() {
+        let backend = MockBackend::new().with_events(vec![
+            TransactionEvent::Percentage(101),
+            TransactionEvent::Status(Status::Install),
+            TransactionEvent::ItemProgress {
+                package_id: String::from("hello;2.10-3;amd64;local"),
+                status: Status::Install,
+                percentage: 50,
+            },
+            TransactionEvent::Percentage(40),
+            TransactionEvent::ElapsedTime(3),
+            TransactionEvent::Percentage(100),
+            TransactionEvent::Finished {
+                exit: Exit::Success,
+                runtime: 1200,
+            },
+        ]);
+        let mut app = app_with(backend.clone()).await;
+        assert_eq!(app.packages().len(), 1);
+
+        let messages = run_install(&mut app).await;
+        let progress: Vec<&TransactionEvent> = messages
+            .iter()
+            .filter_map(|message| match message {
+                Message::Progress(event) => Some(event),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(progress.len(), 6);
+        assert!(progress.iter().any(|event| matches!(
+            event,
+            TransactionEvent::ItemProgress {
+                status: Status::Install,
+                percentage: 50,
+                ..
+            }
+        )));
+        assert!(matches!(messages.last(), Some(Message::PackagesInstalled)));
+
+        assert_eq!(backend.installs(), [(vec![path(&app)], Vec::new())]);
+        assert!(app.is_installed);
+        assert!(!app.ask_install);
+        assert!(app.progress.is_none());
+        assert!(app.packages().is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_install_keeps_packages() {
+        let backend = MockBackend::new().with_events(vec![
+            TransactionEvent::Percentage(10),
+            TransactionEvent::ErrorCode {
+                code: Error::DepResolutionFailed,
+                details: String::from("hello: Depends: libc6 (>= 9.0) but it is not installable"),
+            },
+            TransactionEvent::Finished {
+                exit: Exit::Failed,
+                runtime: 300,
+            },
+        ]);
+        let mut app = app_with(backend).await;
+
+        let messages = run_install(&mut app).await;
+        assert!(matches!(
+            messages.last(),
+            Some(Message::InstallationFailed(TransactionError {
+                code: Error::DepResolutionFailed,
+                ..
+            }))
+        ));
+
+        assert!(!app.is_installed);
+        assert!(!app.ask_install);
+        assert_eq!(app.packages().len(), 1);
+
+        let error = app.error.clone().unwrap();
+        let report = app.error_report(&error);
+        assert!(report.starts_with("error code: dep-resolution-failed\nhello: Depends: libc6"));
+        assert!(report.contains(&path(&app)));
+
+        let diagnosis = unmet::diagnose(app.backend.clone(), error.details, vec![path(&app)]).await;
+        let _ = app.update(Message::Diagnosed(diagnosis));
+        let unmet = &app.diagnosis.as_ref().unwrap().unmet;
+        assert_eq!(unmet[0].relation(), "libc6 (>= 9.0)");
+
+        let _ = app.update(Message::DismissError);
+        assert!(app.error.is_none());
+        assert!(app.diagnosis.is_none());
+    }
+
+    #[tokio::test]
+    async fn enabling_a_source_simulates_again() {
+        let backend = MockBackend::new().with_repository("debian-stable-backports-main", false);
+        let mut app = app_with(backend.clone()).await;
+        let _ = app.update(Message::InstallationFailed(TransactionError {
+            code: Error::DepResolutionFailed,
+            details: String::from("hello: Depends: libc6 (>= 9.0) but it is not installable"),
+        }));
+        let diagnosis = unmet::diagnose(
+            app.backend.clone(),
+            app.error.clone().unwrap().details,
+            Vec::new(),
+        )
+        .await;
+        let _ = app.update(Message::Diagnosed(diagnosis));
+        let repository = app.diagnosis.as_ref().unwrap().disabled[0].clone();
+
+        let _ = app.update(Message::EnableRepository(repository));
+        assert!(app.enable_repository.is_some());
+        let _ = app.update(Message::ConfirmEnableRepository);
+        assert!(app.enable_repository.is_none());
+        assert!(app.is_enabling);
+
+        let result = enable_and_refresh(
+            app.backend.clone(),
+            String::from("debian-stable-backports-main"),
+        )
+        .await
+        .map_err(TransactionError::from);
+        let _ = app.update(Message::RepositoryEnabled(result));
+
+        assert_eq!(backend.enabled(), ["debian-stable-backports-main"]);
+        assert!(!app.is_enabling);
+        assert!(app.error.is_none());
+        assert!(app.confirm_install);
+    }
+
+    #[tokio::test]
+    async fn missing_file_fails_to_load() {
+        let missing = temp_path("missing_1.0_amd64.deb");
+        let mut app = app_with(MockBackend::new()).await;
+        add_file(&mut app, &missing).await;
+
+        assert_eq!(app.files.len(), 2);
+        assert!(matches!(app.files[1].state, FileState::Failed(_)));
+        assert_eq!(app.packages().len(), 1);
+
+        let _ = app.update(Message::RetryFile(missing.clone()));
+        assert!(matches!(app.files[1].state, FileState::Loading));
+
+        let _ = app.update(Message::RemoveFile(missing.clone()));
+        assert_eq!(app.files.len(), 1);
+
+        // A result arriving after the file was removed is dropped.
+        let _ = app.update(Message::PackagesLoaded(missing.clone(), Ok(Vec::new())));
+        assert_eq!(app.files.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn reinstall_needs_confirmation() {
+        let backend = MockBackend::new()
+            .with_installed("hello;2.10-3;amd64;installed:debian-stable-main")
+            .with_events(vec![TransactionEvent::Finished {
+                exit: Exit::Success,
+                runtime: 300,
+            }]);
+        let mut app = app_with(backend.clone()).await;
+        assert_eq!(app.packages()[0].action(), Action::Reinstall);
+        // Skipping the confirmation does not apply to reinstalls.
+        app.config.confirm_install = false;
+
+        ask_installation(&mut app).await;
+        assert!(app.preview.is_some());
+
+        let _ = app.update(Message::CancelConfirmation);
+        assert!(!app.confirm_install);
+        assert!(!app.ask_install);
+
+        ask_installation(&mut app).await;
+        let _ = app.update(Message::ConfirmInstallation);
+        assert!(app.ask_install);
+
+        let (mut msg_tx, _msg_rx) = mpsc::channel(64);
+        install(app.backend.clone(), app.packages(), &mut msg_tx).await;
+        assert_eq!(
+            backend.installs(),
+            [(vec![path(&app)], vec![TransactionFlag::AllowReinstall])]
+        );
+    }
+
+    #[tokio::test]
+    async fn confirmation_can_be_skipped() {
+        let mut app = app_with(MockBackend::new()).await;
+        app.config.confirm_install = false;
+
+        let _ = app.update(Message::AskInstallation);
+        check_cache(&mut app).await;
+        assert!(!app.confirm_install);
+        assert!(app.ask_install);
+    }
+
+    #[tokio::test]
+    async fn stale_lists_are_refreshed_first() {
+        let backend = MockBackend::new().with_cache_age(Duration::from_secs(3 * 24 * 60 * 60));
+        let mut app = app_with(backend.clone()).await;
+        app.config.max_cache_age_hours = 24;
+
+        let _ = app.update(Message::AskInstallation);
+        check_cache(&mut app).await;
+        assert!(app.is_refreshing);
+        assert!(app.progress.is_some());
+        assert!(!app.confirm_install);
+
+        let (mut msg_tx, msg_rx) = mpsc::channel(64);
+        refresh(app.backend.clone(), &mut msg_tx).await;
+        drop(msg_tx);
+        for message in msg_rx.collect::<Vec<_>>().await {
+            let _ = app.update(message);
+        }
+
+        assert_eq!(backend.refreshes(), 1);
+        assert!(!app.is_refreshing);
+        assert!(app.progress.is_none());
+        assert!(app.confirm_install);
+
+        // Asking again later, as a long-running window does, checks the lists again.
+        let _ = app.update(Message::CancelConfirmation);
+        let _ = app.update(Message::AskInstallation);
+        assert!(!app.confirm_install);
+        check_cache(&mut app).await;
+        assert!(app.is_refreshing);
+
+        // A failed refresh is not reported as a failed installation.
+        let error = TransactionError {
+            code: Error::NoNetwork,
+            details: String::from("no route to host"),
+        };
+        let _ = app.update(Message::CacheRefreshed(Err(error)));
+        assert_eq!(app.failed, Operation::Refresh);
+        assert_eq!(app.failed.failed_text(), fl!("refresh-failed"));
+
+        assert!(cache_is_stale(None, 24));
+        assert!(!cache_is_stale(Some(Duration::from_secs(60)), 24));
+    }
+
+    #[tokio::test]
+    async fn repository_version_can_be_chosen() {
+        let backend = MockBackend::new().with_available("hello;2.10-4;amd64;debian-stable-main");
+        let mut app = app_with(backend.clone()).await;
+        let package = app.packages()[0].clone();
+        assert!(package.available.is_some());
+
+        let _ = app.update(Message::UseRepository(path(&app), package.id, true));
+        assert!(app.packages()[0].use_repository);
+
+        ask_installation(&mut app).await;
+        let preview = app.preview.clone().unwrap();
+        assert_eq!(preview.install[0].origin(), "debian-stable-main");
+        let _ = app.update(Message::ConfirmInstallation);
+
+        let (mut msg_tx, _msg_rx) = mpsc::channel(64);
+        install(app.backend.clone(), app.packages(), &mut msg_tx).await;
+        assert_eq!(backend.repository_installs().len(), 1);
+        assert!(backend.installs().is_empty());
+    }
+
+    #[tokio::test]
+    async fn foreign_packages_are_blocked() {
+        let mut app = app_with(MockBackend::new()).await;
+        assert!(!app.has_incompatible());
+
+        let _ = app.update(Message::ArchitecturesDetected(Architectures {
+            native: String::from("arm64"),
+            foreign: Vec::new(),
+        }));
+        assert_eq!(app.support(&app.packages()[0]), Support::Unsupported);
+        assert!(app.has_incompatible());
+
+        if let FileState::Loaded(packages) = &mut app.files[0].state {
+            packages[0].id.arch = String::from("i386");
+        }
+        app.update_selection();
+        let _ = app.update(Message::ArchitecturesDetected(Architectures {
+            native: String::from("amd64"),
+            foreign: Vec::new(),
+        }));
+        assert_eq!(app.support(&app.packages()[0]), Support::NotEnabled);
+        assert!(app.has_incompatible());
+
+        let _ = app.update(Message::ArchitectureEnabled(String::from("i386"), Ok(())));
+        assert_eq!(app.support(&app.packages()[0]), Support::Enabled);
+        assert!(!app.has_incompatible());
+    }
+
+    #[tokio::test]
+    async fn details_show_clashes() {
+        let mut app = app_with(MockBackend::new()).await;
+        let status = Database::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/dpkg/status"
+        ))
+        .unwrap();
+        let mut package = app.packages()[0].clone();
+        package.conflicts = String::from("mail-transport-agent");
+        let _ = app.update(Message::ShowDetails(Box::new(package)));
+        assert!(app.clashes.is_empty());
+
+        // The clashes are worked out once the status is read, not while rendering.
+        let _ = app.update(Message::StatusRead(Arc::new(status)));
+        assert_eq!(level_text(app.clashes[0].level()), fl!("level-danger"));
+        assert!(clash_text(&app.clashes[0]).contains("postfix"));
+        assert!(app.details().is_some());
+
+        let package = app.package.clone().unwrap();
+        let _ = app.update(Message::ShowDetails(Box::new(package)));
+        assert!(app.clashes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn details_list_package_files() {
+        let mut app = app_with(MockBackend::new()).await;
+        let package = app.packages()[0].clone();
+        let _ = app.update(Message::ShowDetails(Box::new(package)));
+        assert!(app.contents.is_none());
+
+        let file = |path: &str, kind| Entry {
+            path: path.to_string(),
+            kind,
+            size: 10,
+            mode: 0o755,
+            exists: false,
+        };
+        let contents = Contents {
+            entries: vec![
+                file("/usr", EntryKind::Directory),
+                file("/usr/bin", EntryKind::Directory),
+                file("/usr/bin/hello", EntryKind::File),
+            ],
+        };
+        let loaded =
+            |path: &str| Message::ContentsLoaded(path.to_string(), Ok(Arc::new(contents.clone())));
+        let _ = app.update(loaded("/tmp/other.deb"));
+        assert!(app.contents.is_none());
+        let _ = app.update(loaded(&path(&app)));
+        assert!(matches!(app.contents, Some(Ok(_))));
+
+        let files = app.details_tabs.iter().nth(1).unwrap();
+        let _ = app.update(Message::SelectDetailsTab(files));
+        assert_eq!(
+            app.details_tabs.active_data::<DetailsTab>(),
+            Some(&DetailsTab::Files)
+        );
+        let _ = app.update(Message::ToggleDirectory(String::from("/usr/bin")));
+        assert!(app.collapsed.contains("/usr/bin"));
+        let _ = app.update(Message::SearchContents(String::from("hello")));
+        assert!(app.details().is_some());
+        let _ = app.update(Message::ToggleDirectory(String::from("/usr/bin")));
+        assert!(app.collapsed.is_empty());
+
+        // Closing and opening the details again starts over.
+        let package = app.packages()[0].clone();
+        let _ = app.update(Message::ShowDetails(Box::new(package.clone())));
+        let _ = app.update(Message::ShowDetails(Box::new(package)));
+        assert!(app.contents.is_none());
+        assert!(app.contents_search.is_empty());
+    }
+
+    #[tokio::test]
+    async fn selection_follows_the_files() {
+        let lib = temp_path("libhello_1.0_amd64.deb");
+        std::fs::write(&lib, b"!<arch>\nlibhello").unwrap();
+        let mut details = hello();
+        details.package_id = String::from("libhello;1.0;amd64;local");
+        let mut app = app_with(MockBackend::new().with_details(&lib, vec![details])).await;
+        add_file(&mut app, &lib).await;
+        std::fs::remove_file(&lib).unwrap();
+
+        if let FileState::Loaded(packages) = &mut app.files[0].state {
+            packages[0].depends = String::from("libhello (>= 1.0)");
+        }
+        app.update_selection();
+        assert_eq!(app.selected.len(), 2);
+        assert_eq!(app.dependencies[0].provider, Provider::Selected(1));
+
+        let _ = app.update(Message::RemoveFile(lib));
+        assert_eq!(app.selected.len(), 1);
+        assert_eq!(app.dependencies[0].provider, Provider::System);
+    }
+
+    #[tokio::test]
+    async fn preview_lists_dependencies() {
+        let backend = MockBackend::new()
+            .with_simulated(Info::Installing, "hello;2.10-3;amd64;local")
+            .with_simulated(
+                Info::Removing,
+                "hello-traditional;2.10-5;amd64;installed:debian-stable-main",
+            );
+        let mut app = app_with(backend.clone()).await;
+
+        ask_installation(&mut app).await;
+        let preview = app.preview.clone().unwrap();
+        assert_eq!(preview.remove[0].id.name, "hello-traditional");
+        assert!(backend.installs().is_empty());
+
+        // A result arriving after the user cancelled is dropped.
+        let _ = app.update(Message::CancelConfirmation);
+        let _ = app.update(Message::PreviewLoaded(Ok(preview)));
+        assert!(app.preview.is_none());
+    }
+
+    #[tokio::test]
+    async fn cancelled_install_keeps_packages() {
+        let backend = MockBackend::new().with_events(vec![
+            TransactionEvent::AllowCancel(true),
+            TransactionEvent::Percentage(10),
+            TransactionEvent::ErrorCode {
+                code: Error::TransactionCancelled,
+                details: String::from("The task was stopped successfully"),
+            },
+            TransactionEvent::Finished {
+                exit: Exit::Cancelled,
+                runtime: 300,
+            },
+        ]);
+        let mut app = app_with(backend).await;
+
+        let messages = run_install(&mut app).await;
+        assert!(messages
+            .iter()
+            .any(|message| matches!(message, Message::AllowCancel(true))));
+        assert!(matches!(
+            messages.last(),
+            Some(Message::InstallationCancelled)
+        ));
+
+        assert!(app.is_cancelled);
+        assert!(!app.is_installed);
+        assert!(!app.ask_install);
+        assert!(!app.allow_cancel);
+        assert_eq!(app.packages().len(), 1);
     }
 }
@@ -0,0 +1,143 @@
+//! The architectures dpkg installs packages for, and adding foreign ones.
+//!
+//! https://wiki.debian.org/Multiarch/HOWTO
+
+use anyhow::{bail, Context};
+use tokio::process::Command;
+
+/// Foreign architectures every machine of the native one can run, i.e. i386 on amd64.
+///
+/// Many arm64 CPUs cannot run 32-bit code, so armhf and armel are only offered once dpkg
+/// lists them.
+fn runnable(native: &str) -> &'static [&'static str] {
+    match native {
+        "amd64" => &["i386"],
+        _ => &[],
+    }
+}
+
+/// Whether a package's architecture can be installed on this system.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Support {
+    /// The native architecture, or `all`.
+    Native,
+    /// A foreign architecture dpkg is configured for.
+    Enabled,
+    /// A foreign architecture the machine can run, but dpkg is not configured for.
+    NotEnabled,
+    /// An architecture the machine cannot run.
+    Unsupported,
+}
+
+/// The native architecture of dpkg and the foreign ones added to it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Architectures {
+    pub native: String,
+    pub foreign: Vec<String>,
+}
+
+impl Architectures {
+    /// Asks dpkg for its architectures.
+    pub async fn detect() -> anyhow::Result<Self> {
+        let native = dpkg(&["--print-architecture"]).await?;
+        let foreign = dpkg(&["--print-foreign-architectures"]).await?;
+        Ok(Self::parse(&native, &foreign))
+    }
+
+    /// Reads the output of `dpkg --print-architecture` and `--print-foreign-architectures`.
+    fn parse(native: &str, foreign: &str) -> Self {
+        Self {
+            native: native.trim().to_string(),
+            foreign: foreign.split_whitespace().map(str::to_string).collect(),
+        }
+    }
+
+    /// Whether packages for `arch` can be installed.
+    pub fn support(&self, arch: &str) -> Support {
+        if arch == "all" || arch == self.native {
+            Support::Native
+        } else if self.foreign.iter().any(|foreign| foreign == arch) {
+            Support::Enabled
+        } else if runnable(&self.native).contains(&arch) {
+            Support::NotEnabled
+        } else {
+            Support::Unsupported
+        }
+    }
+}
+
+/// Runs dpkg and returns what it printed.
+async fn dpkg(args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new("dpkg")
+        .args(args)
+        .output()
+        .await
+        .context("failed to run dpkg")?;
+    if !output.status.success() {
+        bail!(
+            "dpkg {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Adds a foreign architecture to dpkg, asking for authentication through pkexec.
+///
+/// The package lists must be refreshed afterwards to know the architecture's packages.
+pub async fn enable(arch: &str) -> anyhow::Result<()> {
+    if arch.is_empty()
+        || !arch
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        || arch.starts_with('-')
+    {
+        bail!("invalid architecture {arch:?}");
+    }
+
+    let output = Command::new("pkexec")
+        .args(["dpkg", "--add-architecture", arch])
+        .output()
+        .await
+        .context("failed to run pkexec")?;
+    match output.status.code() {
+        Some(0) => Ok(()),
+        // pkexec exits with 126 when the user dismisses the authentication dialog.
+        Some(126) => bail!("authentication was dismissed"),
+        _ => bail!(
+            "failed to add architecture {arch}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tells_architectures_apart() {
+        let architectures = Architectures::parse("amd64\n", "");
+        assert_eq!(architectures.support("amd64"), Support::Native);
+        assert_eq!(architectures.support("all"), Support::Native);
+        assert_eq!(architectures.support("i386"), Support::NotEnabled);
+        assert_eq!(architectures.support("arm64"), Support::Unsupported);
+
+        let architectures = Architectures::parse("arm64\n", "");
+        assert_eq!(architectures.support("armhf"), Support::Unsupported);
+        assert_eq!(architectures.support("armel"), Support::Unsupported);
+
+        let architectures = Architectures::parse("arm64\n", "armhf\ni386\n");
+        assert_eq!(architectures.foreign, ["armhf", "i386"]);
+        assert_eq!(architectures.support("armhf"), Support::Enabled);
+        assert_eq!(architectures.support("armel"), Support::Unsupported);
+        assert_eq!(architectures.support("amd64"), Support::Unsupported);
+    }
+
+    #[tokio::test]
+    async fn rejects_odd_architectures() {
+        assert!(enable("--force-all").await.is_err());
+        assert!(enable("i386 amd64").await.is_err());
+    }
+}
@@ -0,0 +1,323 @@
+//! The package management service used to inspect and install packages.
+
+#[cfg(test)]
+pub mod mock;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::future::BoxFuture;
+use futures_util::{FutureExt, StreamExt};
+use tokio::sync::{Mutex, OnceCell};
+
+use crate::packagekit::{
+    collect_details, collect_packages, collect_repositories, finish, EventStream, Filter,
+    PackageKit, Repository, Role, Transaction, TransactionDetails, TransactionEvent,
+    TransactionFlag, TransactionPackage,
+};
+use crate::polkit::{self, Authorization};
+
+/// Operations the app needs from a package manager.
+///
+/// Methods return boxed futures so the backend can be shared as `Arc<dyn PackageBackend>`
+/// between the model and its subscriptions.
+pub trait PackageBackend: std::fmt::Debug + Send + Sync {
+    /// Reads the packages contained in local files.
+    fn details(
+        &self,
+        paths: Vec<String>,
+    ) -> BoxFuture<'static, anyhow::Result<Vec<TransactionDetails>>>;
+
+    /// Reads the details of packages from the repositories, by their package IDs.
+    fn package_details(
+        &self,
+        package_ids: Vec<String>,
+    ) -> BoxFuture<'static, anyhow::Result<Vec<TransactionDetails>>>;
+
+    /// Looks up packages by name, keeping those that match every filter.
+    fn resolve(
+        &self,
+        filters: Vec<Filter>,
+        names: Vec<String>,
+    ) -> BoxFuture<'static, anyhow::Result<Vec<TransactionPackage>>>;
+
+    /// Lists the configured software sources, enabled or not.
+    fn repositories(&self) -> BoxFuture<'static, anyhow::Result<Vec<Repository>>>;
+
+    /// Enables a software source.
+    fn enable_repository(&self, repo_id: String) -> BoxFuture<'static, anyhow::Result<()>>;
+
+    /// Downloads the package lists of the enabled software sources.
+    fn refresh_cache(&self) -> BoxFuture<'static, anyhow::Result<EventStream>>;
+
+    /// How long ago the package lists were refreshed, or `None` if they never were.
+    fn cache_age(&self) -> BoxFuture<'static, anyhow::Result<Option<Duration>>>;
+
+    /// Resolves what installing local files would do, without changing the system.
+    ///
+    /// The stream reports every package the install would touch as a `Package` event.
+    fn simulate(
+        &self,
+        paths: Vec<String>,
+        flags: Vec<TransactionFlag>,
+    ) -> BoxFuture<'static, anyhow::Result<EventStream>>;
+
+    /// Installs local files, with flags such as `AllowReinstall`.
+    fn install(
+        &self,
+        paths: Vec<String>,
+        flags: Vec<TransactionFlag>,
+    ) -> BoxFuture<'static, anyhow::Result<EventStream>>;
+
+    /// Resolves what installing packages from the repositories would do, by their package IDs.
+    fn simulate_packages(
+        &self,
+        package_ids: Vec<String>,
+    ) -> BoxFuture<'static, anyhow::Result<EventStream>>;
+
+    /// Installs packages from the repositories by their package IDs.
+    fn install_packages(
+        &self,
+        package_ids: Vec<String>,
+    ) -> BoxFuture<'static, anyhow::Result<EventStream>>;
+
+    /// Removes installed packages by their package IDs.
+    // Nothing in the app removes packages yet.
+    #[allow(dead_code)]
+    fn remove(
+        &self,
+        package_ids: Vec<String>,
+        flags: Vec<TransactionFlag>,
+    ) -> BoxFuture<'static, anyhow::Result<EventStream>>;
+
+    /// Cancels the install, removal or refresh that is currently running, if any.
+    fn cancel(&self) -> BoxFuture<'static, anyhow::Result<()>>;
+
+    /// Whether installing local files needs the user to authenticate.
+    fn authorization(&self) -> BoxFuture<'static, anyhow::Result<Authorization>>;
+}
+
+/// A backend talking to the PackageKit daemon on the system bus.
+#[derive(Debug, Default)]
+pub struct PackageKitBackend {
+    client: Arc<OnceCell<PackageKit>>,
+    running: Arc<Mutex<Option<Transaction>>>,
+}
+
+impl PackageKitBackend {
+    /// Connects to PackageKit on first use and creates a new transaction.
+    fn transaction(&self) -> BoxFuture<'static, anyhow::Result<Transaction>> {
+        let client = self.client.clone();
+        async move {
+            let pk = client.get_or_try_init(PackageKit::new).await?;
+            pk.transaction().await
+        }
+        .boxed()
+    }
+
+    /// Creates a transaction, started by `start`, that `cancel` reaches until it finishes.
+    fn cancellable(
+        &self,
+        start: impl FnOnce(Transaction) -> BoxFuture<'static, anyhow::Result<EventStream>>
+            + Send
+            + 'static,
+    ) -> BoxFuture<'static, anyhow::Result<EventStream>> {
+        let transaction = self.transaction();
+        let running = self.running.clone();
+        async move {
+            let tx = transaction.await?;
+            let path = tx.path();
+            *running.lock().await = Some(tx.clone());
+
+            let events = match start(tx).await {
+                Ok(events) => events,
+                Err(why) => {
+                    forget(&running, &path).await;
+                    return Err(why);
+                }
+            };
+            let events = events.then(move |event| {
+                let running = running.clone();
+                let path = path.clone();
+                async move {
+                    if matches!(event, Ok(TransactionEvent::Finished { .. }) | Err(_)) {
+                        forget(&running, &path).await;
+                    }
+                    event
+                }
+            });
+            Ok(events.boxed())
+        }
+        .boxed()
+    }
+}
+
+/// Clears the running transaction if it is still the one at `path`.
+async fn forget(running: &Mutex<Option<Transaction>>, path: &str) {
+    let mut running = running.lock().await;
+    if running.as_ref().is_some_and(|tx| tx.path() == path) {
+        *running = None;
+    }
+}
+
+impl PackageBackend for PackageKitBackend {
+    fn details(
+        &self,
+        paths: Vec<String>,
+    ) -> BoxFuture<'static, anyhow::Result<Vec<TransactionDetails>>> {
+        let transaction = self.transaction();
+        async move {
+            let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+            let tx = transaction.await?;
+            collect_details(tx.get_details_local(&paths).await?).await
+        }
+        .boxed()
+    }
+
+    fn package_details(
+        &self,
+        package_ids: Vec<String>,
+    ) -> BoxFuture<'static, anyhow::Result<Vec<TransactionDetails>>> {
+        let transaction = self.transaction();
+        async move {
+            let package_ids: Vec<&str> = package_ids.iter().map(String::as_str).collect();
+            let tx = transaction.await?;
+            collect_details(tx.get_details(&package_ids).await?).await
+        }
+        .boxed()
+    }
+
+    fn resolve(
+        &self,
+        filters: Vec<Filter>,
+        names: Vec<String>,
+    ) -> BoxFuture<'static, anyhow::Result<Vec<TransactionPackage>>> {
+        let transaction = self.transaction();
+        async move {
+            let names: Vec<&str> = names.iter().map(String::as_str).collect();
+            let tx = transaction.await?;
+            collect_packages(tx.resolve(&filters, &names).await?).await
+        }
+        .boxed()
+    }
+
+    fn repositories(&self) -> BoxFuture<'static, anyhow::Result<Vec<Repository>>> {
+        let transaction = self.transaction();
+        async move { collect_repositories(transaction.await?.get_repo_list().await?).await }.boxed()
+    }
+
+    fn enable_repository(&self, repo_id: String) -> BoxFuture<'static, anyhow::Result<()>> {
+        let transaction = self.transaction();
+        async move { finish(transaction.await?.repo_enable(&repo_id, true).await?).await }.boxed()
+    }
+
+    fn refresh_cache(&self) -> BoxFuture<'static, anyhow::Result<EventStream>> {
+        self.cancellable(|tx| async move { tx.refresh_cache(false).await }.boxed())
+    }
+
+    fn cache_age(&self) -> BoxFuture<'static, anyhow::Result<Option<Duration>>> {
+        let client = self.client.clone();
+        async move {
+            let pk = client.get_or_try_init(PackageKit::new).await?;
+            pk.time_since_action(Role::RefreshCache).await
+        }
+        .boxed()
+    }
+
+    fn simulate(
+        &self,
+        paths: Vec<String>,
+        mut flags: Vec<TransactionFlag>,
+    ) -> BoxFuture<'static, anyhow::Result<EventStream>> {
+        let transaction = self.transaction();
+        async move {
+            let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+            flags.push(TransactionFlag::Simulate);
+            transaction.await?.install_files(&flags, &paths).await
+        }
+        .boxed()
+    }
+
+    fn install(
+        &self,
+        paths: Vec<String>,
+        flags: Vec<TransactionFlag>,
+    ) -> BoxFuture<'static, anyhow::Result<EventStream>> {
+        self.cancellable(|tx| {
+            async move {
+                let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+                let flags = if flags.is_empty() {
+                    vec![TransactionFlag::None]
+                } else {
+                    flags
+                };
+                tx.install_files(&flags, &paths).await
+            }
+            .boxed()
+        })
+    }
+
+    fn simulate_packages(
+        &self,
+        package_ids: Vec<String>,
+    ) -> BoxFuture<'static, anyhow::Result<EventStream>> {
+        let transaction = self.transaction();
+        async move {
+            let package_ids: Vec<&str> = package_ids.iter().map(String::as_str).collect();
+            let flags = [TransactionFlag::OnlyTrusted, TransactionFlag::Simulate];
+            transaction
+                .await?
+                .install_packages(&flags, &package_ids)
+                .await
+        }
+        .boxed()
+    }
+
+    fn install_packages(
+        &self,
+        package_ids: Vec<String>,
+    ) -> BoxFuture<'static, anyhow::Result<EventStream>> {
+        self.cancellable(|tx| {
+            async move {
+                let package_ids: Vec<&str> = package_ids.iter().map(String::as_str).collect();
+                let flags = [TransactionFlag::OnlyTrusted];
+                tx.install_packages(&flags, &package_ids).await
+            }
+            .boxed()
+        })
+    }
+
+    fn remove(
+        &self,
+        package_ids: Vec<String>,
+        flags: Vec<TransactionFlag>,
+    ) -> BoxFuture<'static, anyhow::Result<EventStream>> {
+        self.cancellable(|tx| {
+            async move {
+                let package_ids: Vec<&str> = package_ids.iter().map(String::as_str).collect();
+                let flags = if flags.is_empty() {
+                    vec![TransactionFlag::None]
+                } else {
+                    flags
+                };
+                tx.remove_packages(&flags, &package_ids).await
+            }
+            .boxed()
+        })
+    }
+
+    fn cancel(&self) -> BoxFuture<'static, anyhow::Result<()>> {
+        let running = self.running.clone();
+        async move {
+            match running.lock().await.as_ref() {
+                Some(tx) => tx.cancel().await,
+                None => Ok(()),
+            }
+        }
+        .boxed()
+    }
+
+    fn authorization(&self) -> BoxFuture<'static, anyhow::Result<Authorization>> {
+        polkit::check_install().boxed()
+    }
+}
@@ -0,0 +1,333 @@
+//! A scripted backend for exercising the app without a PackageKit daemon.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::anyhow;
+use futures_util::future::BoxFuture;
+use futures_util::{stream, FutureExt, StreamExt};
+
+use super::PackageBackend;
+use crate::packagekit::{
+    EventStream, Exit, Filter, Info, Repository, TransactionDetails, TransactionEvent,
+    TransactionFlag, TransactionPackage,
+};
+use crate::polkit::Authorization;
+
+/// Replays canned details and transaction events, recording what it was asked to install.
+#[derive(Debug, Default, Clone)]
+pub struct MockBackend {
+    details: HashMap<String, Vec<TransactionDetails>>,
+    package_details: Vec<TransactionDetails>,
+    installed: Vec<TransactionPackage>,
+    available: Vec<TransactionPackage>,
+    simulation: Vec<TransactionPackage>,
+    repositories: Vec<Repository>,
+    events: Vec<TransactionEvent>,
+    installs: Arc<Mutex<Vec<Install>>>,
+    removals: Arc<Mutex<Vec<Removal>>>,
+    transactions: Arc<Mutex<Vec<&'static str>>>,
+    repository_installs: Arc<Mutex<Vec<Vec<String>>>>,
+    enabled: Arc<Mutex<Vec<String>>>,
+    cache_age: Duration,
+    refreshes: Arc<Mutex<usize>>,
+    authorization: Option<Authorization>,
+}
+
+/// The files and flags of an `install` call.
+pub type Install = (Vec<String>, Vec<TransactionFlag>);
+
+/// The package IDs and flags of a `remove` call.
+pub type Removal = (Vec<String>, Vec<TransactionFlag>);
+
+impl MockBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes `details` return these packages for the file at `path`.
+    ///
+    /// Files without details fail to load.
+    pub fn with_details(mut self, path: &str, details: Vec<TransactionDetails>) -> Self {
+        self.details.insert(path.to_string(), details);
+        self
+    }
+
+    /// Makes `package_details` return these details when asked for their package IDs.
+    pub fn with_package_details(mut self, details: TransactionDetails) -> Self {
+        self.package_details.push(details);
+        self
+    }
+
+    /// Makes `simulate` report this package, e.g. with `Info::Installing`.
+    pub fn with_simulated(mut self, info: Info, package_id: &str) -> Self {
+        self.simulation.push(TransactionPackage {
+            info,
+            package_id: package_id.to_string(),
+            summary: String::new(),
+        });
+        self
+    }
+
+    /// Adds a software source to what `repositories` reports.
+    pub fn with_repository(mut self, id: &str, enabled: bool) -> Self {
+        self.repositories.push(Repository {
+            id: id.to_string(),
+            description: id.replace('-', " "),
+            enabled,
+        });
+        self
+    }
+
+    /// Makes `resolve` report this package as installed.
+    pub fn with_installed(mut self, package_id: &str) -> Self {
+        self.installed.push(TransactionPackage {
+            info: Info::Installed,
+            package_id: package_id.to_string(),
+            summary: String::new(),
+        });
+        self
+    }
+
+    /// Makes `resolve` with `Filter::NotInstalled` report this package from a repository.
+    pub fn with_available(mut self, package_id: &str) -> Self {
+        self.available.push(TransactionPackage {
+            info: Info::Available,
+            package_id: package_id.to_string(),
+            summary: String::new(),
+        });
+        self
+    }
+
+    /// How old `cache_age` says the package lists are, fresh unless set.
+    pub fn with_cache_age(mut self, age: Duration) -> Self {
+        self.cache_age = age;
+        self
+    }
+
+    /// What `authorization` reports, `Authorized` unless set.
+    pub fn with_authorization(mut self, authorization: Authorization) -> Self {
+        self.authorization = Some(authorization);
+        self
+    }
+
+    /// Events replayed by every install and removal.
+    pub fn with_events(mut self, events: Vec<TransactionEvent>) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// The paths and flags given to each call of `install`.
+    pub fn installs(&self) -> Vec<Install> {
+        self.installs.lock().unwrap().clone()
+    }
+
+    /// The package IDs and flags given to each call of `remove`.
+    pub fn removals(&self) -> Vec<Removal> {
+        self.removals.lock().unwrap().clone()
+    }
+
+    /// The software sources passed to `enable_repository`.
+    pub fn enabled(&self) -> Vec<String> {
+        self.enabled.lock().unwrap().clone()
+    }
+
+    /// The package IDs passed to each `install_packages` call.
+    pub fn repository_installs(&self) -> Vec<Vec<String>> {
+        self.repository_installs.lock().unwrap().clone()
+    }
+
+    /// The methods that changed the system, in the order they were called.
+    pub fn transactions(&self) -> Vec<&'static str> {
+        self.transactions.lock().unwrap().clone()
+    }
+
+    /// How many times `refresh_cache` was called.
+    pub fn refreshes(&self) -> usize {
+        *self.refreshes.lock().unwrap()
+    }
+
+    fn replay(events: Vec<TransactionEvent>) -> BoxFuture<'static, anyhow::Result<EventStream>> {
+        let events = stream::iter(events.into_iter().map(Ok)).boxed();
+        async move { Ok(events) }.boxed()
+    }
+}
+
+impl PackageBackend for MockBackend {
+    fn details(
+        &self,
+        paths: Vec<String>,
+    ) -> BoxFuture<'static, anyhow::Result<Vec<TransactionDetails>>> {
+        let result = paths
+            .iter()
+            .map(|path| {
+                self.details
+                    .get(path)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("no such package file {path}"))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map(|details| details.into_iter().flatten().collect());
+        async move { result }.boxed()
+    }
+
+    fn package_details(
+        &self,
+        package_ids: Vec<String>,
+    ) -> BoxFuture<'static, anyhow::Result<Vec<TransactionDetails>>> {
+        let details = self
+            .package_details
+            .iter()
+            .filter(|details| package_ids.contains(&details.package_id))
+            .cloned()
+            .collect();
+        async move { Ok(details) }.boxed()
+    }
+
+    fn resolve(
+        &self,
+        filters: Vec<Filter>,
+        names: Vec<String>,
+    ) -> BoxFuture<'static, anyhow::Result<Vec<TransactionPackage>>> {
+        let packages = if filters.contains(&Filter::NotInstalled) {
+            &self.available
+        } else {
+            &self.installed
+        };
+        let packages = packages
+            .iter()
+            .filter(|package| {
+                let name = package.package_id.split(';').next().unwrap_or_default();
+                names.iter().any(|wanted| wanted == name)
+            })
+            .cloned()
+            .collect();
+        async move { Ok(packages) }.boxed()
+    }
+
+    fn repositories(&self) -> BoxFuture<'static, anyhow::Result<Vec<Repository>>> {
+        let repositories = self.repositories.clone();
+        async move { Ok(repositories) }.boxed()
+    }
+
+    fn enable_repository(&self, repo_id: String) -> BoxFuture<'static, anyhow::Result<()>> {
+        self.enabled.lock().unwrap().push(repo_id);
+        async { Ok(()) }.boxed()
+    }
+
+    fn refresh_cache(&self) -> BoxFuture<'static, anyhow::Result<EventStream>> {
+        *self.refreshes.lock().unwrap() += 1;
+        Self::replay(vec![TransactionEvent::Finished {
+            exit: Exit::Success,
+            runtime: 0,
+        }])
+    }
+
+    fn cache_age(&self) -> BoxFuture<'static, anyhow::Result<Option<Duration>>> {
+        let age = self.cache_age;
+        async move { Ok(Some(age)) }.boxed()
+    }
+
+    fn simulate(
+        &self,
+        _paths: Vec<String>,
+        _flags: Vec<TransactionFlag>,
+    ) -> BoxFuture<'static, anyhow::Result<EventStream>> {
+        let mut events: Vec<TransactionEvent> = self
+            .simulation
+            .iter()
+            .cloned()
+            .map(TransactionEvent::Package)
+            .collect();
+        events.push(TransactionEvent::Finished {
+            exit: Exit::Success,
+            runtime: 0,
+        });
+        Self::replay(events)
+    }
+
+    fn install(
+        &self,
+        paths: Vec<String>,
+        flags: Vec<TransactionFlag>,
+    ) -> BoxFuture<'static, anyhow::Result<EventStream>> {
+        self.installs.lock().unwrap().push((paths, flags));
+        self.transactions.lock().unwrap().push("install");
+        Self::replay(self.events.clone())
+    }
+
+    fn simulate_packages(
+        &self,
+        package_ids: Vec<String>,
+    ) -> BoxFuture<'static, anyhow::Result<EventStream>> {
+        let mut events: Vec<TransactionEvent> = package_ids
+            .into_iter()
+            .map(|package_id| {
+                TransactionEvent::Package(TransactionPackage {
+                    info: Info::Installing,
+                    package_id,
+                    summary: String::new(),
+                })
+            })
+            .collect();
+        events.push(TransactionEvent::Finished {
+            exit: Exit::Success,
+            runtime: 0,
+        });
+        Self::replay(events)
+    }
+
+    fn install_packages(
+        &self,
+        package_ids: Vec<String>,
+    ) -> BoxFuture<'static, anyhow::Result<EventStream>> {
+        self.repository_installs.lock().unwrap().push(package_ids);
+        self.transactions.lock().unwrap().push("install_packages");
+        Self::replay(self.events.clone())
+    }
+
+    fn remove(
+        &self,
+        package_ids: Vec<String>,
+        flags: Vec<TransactionFlag>,
+    ) -> BoxFuture<'static, anyhow::Result<EventStream>> {
+        self.removals.lock().unwrap().push((package_ids, flags));
+        self.transactions.lock().unwrap().push("remove");
+        Self::replay(self.events.clone())
+    }
+
+    fn cancel(&self) -> BoxFuture<'static, anyhow::Result<()>> {
+        async { Ok(()) }.boxed()
+    }
+
+    fn authorization(&self) -> BoxFuture<'static, anyhow::Result<Authorization>> {
+        let authorization = self.authorization.unwrap_or(Authorization::Authorized);
+        async move { Ok(authorization) }.boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn removals_are_recorded_and_replayed() {
+        let backend = MockBackend::new().with_events(vec![
+            TransactionEvent::Percentage(50),
+            TransactionEvent::Finished {
+                exit: Exit::Success,
+                runtime: 10,
+            },
+        ]);
+        let id = String::from("hello;2.10-3;amd64;installed:debian-stable-main");
+
+        let events = backend.remove(vec![id.clone()], Vec::new()).await.unwrap();
+        let events: Vec<_> = events.map(Result::unwrap).collect().await;
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[0], TransactionEvent::Percentage(50)));
+        assert_eq!(backend.removals(), [(vec![id], Vec::new())]);
+        assert!(backend.installs().is_empty());
+        assert_eq!(backend.transactions(), ["remove"]);
+    }
+}
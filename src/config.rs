@@ -2,8 +2,24 @@
 
 use cosmic::cosmic_config::{self, cosmic_config_derive::CosmicConfigEntry, CosmicConfigEntry};
 
-#[derive(Debug, Default, Clone, CosmicConfigEntry, Eq, PartialEq)]
-#[version = 1]
+#[derive(Debug, Clone, CosmicConfigEntry, Eq, PartialEq)]
+#[version = 2]
 pub struct Config {
-    demo: String,
+    /// Whether to show what an install will change and wait for the user to accept it.
+    ///
+    /// Reinstalls and downgrades are confirmed regardless.
+    pub confirm_install: bool,
+    /// Refresh the package lists before installing when they are older than this, in hours.
+    ///
+    /// Zero never refreshes them.
+    pub max_cache_age_hours: u32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            confirm_install: true,
+            max_cache_age_hours: 24,
+        }
+    }
 }
@@ -0,0 +1,212 @@
+//! What installing a package does to the packages already on the system, through the
+//! `Conflicts`, `Breaks` and `Replaces` fields of both.
+//!
+//! https://www.debian.org/doc/debian-policy/ch-relationships.html#conflicting-binary-packages-conflicts
+
+use crate::dpkg::{Database, InstalledPackage};
+use crate::package::Package;
+use crate::packagekit::PackageId;
+use crate::relation::{self, Relation};
+
+/// The relationship field a clash comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// The installed package is removed to make way for the new one.
+    Conflicts,
+    /// The installed package must be upgraded, or it is removed.
+    Breaks,
+    /// The new package takes over files of the installed one.
+    Replaces,
+}
+
+/// How much a clash should worry the user, from least to most.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Notice,
+    Warning,
+    Danger,
+}
+
+/// An installed package affected by installing a selected one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Clash {
+    pub kind: Kind,
+    pub installed: PackageId,
+    /// The relation that matched, as written in the control file.
+    pub relation: String,
+    /// Whether the installed package declared the relation, rather than the selected one.
+    pub reverse: bool,
+}
+
+impl Clash {
+    pub fn level(&self) -> Level {
+        match self.kind {
+            Kind::Conflicts => Level::Danger,
+            Kind::Breaks => Level::Warning,
+            Kind::Replaces => Level::Notice,
+        }
+    }
+}
+
+/// Whether a package with this name, version and `Provides` field meets the relation.
+fn satisfies(name: &str, version: &str, provides: &str, relation: &Relation) -> bool {
+    if name == relation.name {
+        return relation.allows(version);
+    }
+    // Unversioned provides only meet unversioned relations, as for dependencies.
+    relation::parse(provides)
+        .unwrap_or_default()
+        .iter()
+        .flatten()
+        .filter(|provided| provided.name == relation.name)
+        .any(|provided| match &provided.constraint {
+            Some((_, version)) => relation.allows(version),
+            None => relation.constraint.is_none(),
+        })
+}
+
+/// The relations of a field, ignoring alternatives, which these fields do not have.
+fn relations(package: &str, field: &str) -> Vec<Relation> {
+    match relation::parse(field) {
+        Ok(groups) => groups.into_iter().flatten().collect(),
+        Err(why) => {
+            eprintln!("ignoring relations of {package}: {why}");
+            Vec::new()
+        }
+    }
+}
+
+/// Finds the installed packages that installing `package` removes, breaks or takes files from.
+///
+/// Both sides are checked, as dpkg also refuses to install a package that an installed one
+/// conflicts with or breaks. Other versions of the package itself are not clashes, as they
+/// are upgraded. The most worrying clashes come first.
+pub fn analyze(package: &Package, database: &Database) -> Vec<Clash> {
+    let others: Vec<&InstalledPackage> = database
+        .installed()
+        .filter(|installed| installed.name != package.id.name)
+        .collect();
+    let mut clashes = Vec::new();
+
+    let fields = [
+        (Kind::Conflicts, &package.conflicts),
+        (Kind::Breaks, &package.breaks),
+        (Kind::Replaces, &package.replaces),
+    ];
+    for (kind, field) in fields {
+        for relation in relations(&package.id.name, field) {
+            for installed in &others {
+                if satisfies(
+                    &installed.name,
+                    &installed.version,
+                    &installed.provides,
+                    &relation,
+                ) {
+                    clashes.push(Clash {
+                        kind,
+                        installed: installed.id(),
+                        relation: relation.to_string(),
+                        reverse: false,
+                    });
+                }
+            }
+        }
+    }
+
+    for installed in &others {
+        let fields = [
+            (Kind::Conflicts, &installed.conflicts),
+            (Kind::Breaks, &installed.breaks),
+        ];
+        for (kind, field) in fields {
+            for relation in relations(&installed.name, field) {
+                if satisfies(
+                    &package.id.name,
+                    &package.id.version,
+                    &package.provides,
+                    &relation,
+                ) {
+                    clashes.push(Clash {
+                        kind,
+                        installed: installed.id(),
+                        relation: relation.to_string(),
+                        reverse: true,
+                    });
+                }
+            }
+        }
+    }
+
+    clashes.sort_by_key(|clash| std::cmp::Reverse(clash.level()));
+    clashes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packagekit::TransactionDetails;
+
+    const STATUS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/dpkg/status");
+
+    fn package(id: &str) -> Package {
+        let details = TransactionDetails {
+            package_id: id.to_string(),
+            summary: String::new(),
+            description: String::new(),
+            url: String::new(),
+            license: String::new(),
+            size: 0,
+            download_size: 0,
+        };
+        Package::new(String::from("/tmp/package.deb"), details).unwrap()
+    }
+
+    fn summary(clashes: &[Clash]) -> Vec<(Kind, &str, bool)> {
+        clashes
+            .iter()
+            .map(|clash| (clash.kind, clash.installed.name.as_str(), clash.reverse))
+            .collect()
+    }
+
+    #[test]
+    fn finds_clashes_in_both_directions() {
+        let database = Database::read(STATUS).unwrap();
+
+        let mut exim = package("exim4-daemon-light;4.96-15;amd64;local");
+        exim.conflicts = String::from("mail-transport-agent");
+        exim.replaces = String::from("mail-transport-agent");
+        exim.provides = String::from("mail-transport-agent");
+        let clashes = analyze(&exim, &database);
+        assert_eq!(
+            summary(&clashes),
+            [
+                (Kind::Conflicts, "postfix", false),
+                (Kind::Conflicts, "postfix", true),
+                (Kind::Replaces, "postfix", false),
+            ]
+        );
+        assert_eq!(clashes[0].level(), Level::Danger);
+        assert_eq!(clashes[0].relation, "mail-transport-agent");
+
+        let mut nscd = package("nscd;2.35-1;amd64;local");
+        nscd.breaks = String::from("libc6 (<< 2.36)");
+        let clashes = analyze(&nscd, &database);
+        assert_eq!(summary(&clashes), [(Kind::Breaks, "libc6", true)]);
+        assert_eq!(clashes[0].relation, "nscd (<< 2.36)");
+    }
+
+    #[test]
+    fn ignores_versions_outside_the_relation() {
+        let database = Database::read(STATUS).unwrap();
+
+        let mut nscd = package("nscd;2.36-9;amd64;local");
+        nscd.breaks = String::from("libc6 (<< 2.36)");
+        assert!(analyze(&nscd, &database).is_empty());
+
+        // Upgrading postfix itself is not a clash, though it conflicts with what it provides.
+        let mut postfix = package("postfix;3.7.11-0+deb12u1;amd64;local");
+        postfix.conflicts = String::from("mail-transport-agent");
+        postfix.provides = String::from("mail-transport-agent");
+        assert!(analyze(&postfix, &database).is_empty());
+    }
+}
@@ -0,0 +1,389 @@
+//! The files a package puts on disk, read from its `data.tar` archive.
+
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::Context;
+
+use crate::deb::Deb;
+use crate::dpkg;
+
+/// What an entry of the data archive is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EntryKind {
+    Directory,
+    File,
+    /// A symbolic link, with its target as written in the archive.
+    Symlink(String),
+    /// A hard link to another entry of the archive, by its absolute path.
+    Hardlink(String),
+    /// Devices, fifos and other special files.
+    Other,
+}
+
+/// A file or directory the package installs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    /// The absolute path it is installed to, e.g. `/usr/bin/hello`.
+    pub path: String,
+    pub kind: EntryKind,
+    /// The size of a file, or for directories the size of every file below them.
+    pub size: u64,
+    /// The permission bits, including setuid, setgid and sticky.
+    pub mode: u32,
+    /// Whether something that is not a directory already exists at the path.
+    pub exists: bool,
+}
+
+impl Entry {
+    /// The last component of the path.
+    pub fn name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or_default()
+    }
+
+    /// How many directories the entry is below `/`, starting at 0.
+    pub fn depth(&self) -> usize {
+        self.path.matches('/').count() - 1
+    }
+
+    pub fn is_directory(&self) -> bool {
+        self.kind == EntryKind::Directory
+    }
+
+    /// Whether running the file gives the rights of its owner or group.
+    pub fn is_setuid(&self) -> bool {
+        self.mode & 0o6000 != 0 && self.kind == EntryKind::File
+    }
+
+    /// The type and permissions as `ls -l` shows them, e.g. `-rwsr-xr-x`.
+    pub fn permissions(&self) -> String {
+        let kind = match self.kind {
+            EntryKind::Directory => 'd',
+            EntryKind::Symlink(_) => 'l',
+            EntryKind::File | EntryKind::Hardlink(_) | EntryKind::Other => '-',
+        };
+        // Each class, with the special bit that replaces its execute bit and its letters.
+        let classes = [(6, 0o4000, 's'), (3, 0o2000, 's'), (0, 0o1000, 't')];
+
+        let mut permissions = String::from(kind);
+        for (shift, special, letter) in classes {
+            let bits = self.mode >> shift;
+            permissions.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+            permissions.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+            permissions.push(match (bits & 0o1 != 0, self.mode & special != 0) {
+                (true, true) => letter,
+                (false, true) => letter.to_ascii_uppercase(),
+                (true, false) => 'x',
+                (false, false) => '-',
+            });
+        }
+        permissions
+    }
+}
+
+/// Every entry of a package's data archive, each directory before what it contains.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Contents {
+    pub entries: Vec<Entry>,
+}
+
+impl Contents {
+    /// Lists the files of the `.deb` at `path` and checks which of them already exist.
+    pub fn read(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let deb = Deb::open(path)?;
+        let mut contents = deb
+            .read_data(Self::from_data)
+            .with_context(|| format!("failed to list the files of {}", path.display()))?;
+
+        let name = deb.control.get("Package").unwrap_or_default();
+        let arch = deb.control.get("Architecture").unwrap_or_default();
+        let owned = dpkg::files(dpkg::INFO, name, arch).unwrap_or_else(|why| {
+            eprintln!("failed to read the installed files of {name}: {why:#}");
+            HashSet::new()
+        });
+        contents.mark_existing(Path::new("/"), &owned);
+        Ok(contents)
+    }
+
+    /// Lists the entries of a data archive.
+    ///
+    /// Directories the archive leaves out are added, so every entry has its parent.
+    fn from_data(data: &mut dyn Read) -> anyhow::Result<Self> {
+        let mut entries = Vec::new();
+        for entry in tar::Archive::new(data).entries()? {
+            let entry = entry?;
+            let header = entry.header();
+            let path = absolute(&entry.path()?.to_string_lossy());
+            if path == "/" {
+                continue;
+            }
+
+            let target = || {
+                entry
+                    .link_name()
+                    .ok()
+                    .flatten()
+                    .map(|target| target.to_string_lossy().into_owned())
+                    .unwrap_or_default()
+            };
+            let kind = match header.entry_type() {
+                tar::EntryType::Directory => EntryKind::Directory,
+                tar::EntryType::Regular | tar::EntryType::Continuous => EntryKind::File,
+                tar::EntryType::Symlink => EntryKind::Symlink(target()),
+                tar::EntryType::Link => EntryKind::Hardlink(absolute(&target())),
+                _ => EntryKind::Other,
+            };
+            let size = if kind == EntryKind::File {
+                header.size()?
+            } else {
+                0
+            };
+
+            entries.push(Entry {
+                path,
+                kind,
+                size,
+                mode: header.mode()? & 0o7777,
+                exists: false,
+            });
+        }
+
+        let mut known: HashSet<String> = entries.iter().map(|entry| entry.path.clone()).collect();
+        let mut missing = Vec::new();
+        for entry in &entries {
+            let mut path = entry.path.as_str();
+            while let Some((parent, _)) = path.rsplit_once('/').filter(|(p, _)| !p.is_empty()) {
+                if known.insert(parent.to_string()) {
+                    missing.push(Entry {
+                        path: parent.to_string(),
+                        kind: EntryKind::Directory,
+                        size: 0,
+                        mode: 0o755,
+                        exists: false,
+                    });
+                }
+                path = parent;
+            }
+        }
+        entries.extend(missing);
+        entries.sort_by(|a, b| a.path.split('/').cmp(b.path.split('/')));
+
+        let mut sizes: HashMap<String, u64> = HashMap::new();
+        for entry in &entries {
+            let mut path = entry.path.as_str();
+            while let Some((parent, _)) = path.rsplit_once('/').filter(|(p, _)| !p.is_empty()) {
+                *sizes.entry(parent.to_string()).or_default() += entry.size;
+                path = parent;
+            }
+        }
+        for entry in entries.iter_mut().filter(|entry| entry.is_directory()) {
+            entry.size = sizes.get(&entry.path).copied().unwrap_or_default();
+        }
+
+        Ok(Self { entries })
+    }
+
+    /// Marks the entries that would replace something under `root`.
+    ///
+    /// Existing directories are not marked, as packages share them, and neither are the
+    /// `owned` paths the installed version of the package put there.
+    fn mark_existing(&mut self, root: &Path, owned: &HashSet<String>) {
+        for entry in &mut self.entries {
+            entry.exists = !entry.is_directory()
+                && !owned.contains(&entry.path)
+                && root
+                    .join(entry.path.trim_start_matches('/'))
+                    .symlink_metadata()
+                    .is_ok();
+        }
+    }
+
+    /// How many files, links and other entries that are not directories there are.
+    pub fn files(&self) -> usize {
+        self.entries.iter().filter(|e| !e.is_directory()).count()
+    }
+
+    /// How many entries would replace something already on the system.
+    pub fn existing(&self) -> usize {
+        self.entries.iter().filter(|e| e.exists).count()
+    }
+
+    /// The entries to show, leaving out what is inside `collapsed` directories.
+    ///
+    /// With a search, only the entries whose path contains it are shown, whether collapsed
+    /// or not. The search ignores case.
+    pub fn visible<'a>(&'a self, collapsed: &HashSet<String>, search: &str) -> Vec<&'a Entry> {
+        let search = search.trim().to_lowercase();
+        if !search.is_empty() {
+            return self
+                .entries
+                .iter()
+                .filter(|entry| entry.path.to_lowercase().contains(&search))
+                .collect();
+        }
+
+        let mut visible = Vec::new();
+        let mut hidden_below: Option<String> = None;
+        for entry in &self.entries {
+            if let Some(directory) = &hidden_below {
+                if entry.path.starts_with(directory) {
+                    continue;
+                }
+                hidden_below = None;
+            }
+            if entry.is_directory() && collapsed.contains(&entry.path) {
+                hidden_below = Some(format!("{}/", entry.path));
+            }
+            visible.push(entry);
+        }
+        visible
+    }
+}
+
+/// Turns a path of the archive like `./usr/bin/` into `/usr/bin`.
+fn absolute(path: &str) -> String {
+    let path = path.trim_start_matches("./").trim_matches('/');
+    format!("/{path}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A data archive in the shape dpkg-deb builds, but with some directories left out.
+    fn archive() -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        let mut add = |kind, path: &str, mode, data: &[u8], target: Option<&str>| {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(kind);
+            header.set_mode(mode);
+            header.set_size(data.len() as u64);
+            match target {
+                Some(target) => builder.append_link(&mut header, path, target).unwrap(),
+                None => builder.append_data(&mut header, path, data).unwrap(),
+            }
+        };
+        add(tar::EntryType::Directory, "./", 0o755, b"", None);
+        add(tar::EntryType::Directory, "./usr/", 0o755, b"", None);
+        add(tar::EntryType::Directory, "./usr/bin/", 0o755, b"", None);
+        add(
+            tar::EntryType::Regular,
+            "./usr/bin/hello",
+            0o4755,
+            b"#!/bin/sh\n",
+            None,
+        );
+        let link = Some("hello");
+        add(tar::EntryType::Symlink, "./usr/bin/hi", 0o777, b"", link);
+        let copyright = "./usr/share/doc/hello/copyright";
+        add(tar::EntryType::Regular, copyright, 0o644, b"GPL", None);
+        builder.into_inner().unwrap()
+    }
+
+    fn contents() -> Contents {
+        let data = archive();
+        Contents::from_data(&mut data.as_slice()).unwrap()
+    }
+
+    fn paths(entries: &[&Entry]) -> Vec<String> {
+        entries.iter().map(|entry| entry.path.clone()).collect()
+    }
+
+    #[test]
+    fn lists_entries_as_a_tree() {
+        let contents = contents();
+        let all: Vec<&Entry> = contents.entries.iter().collect();
+        assert_eq!(
+            paths(&all),
+            [
+                "/usr",
+                "/usr/bin",
+                "/usr/bin/hello",
+                "/usr/bin/hi",
+                "/usr/share",
+                "/usr/share/doc",
+                "/usr/share/doc/hello",
+                "/usr/share/doc/hello/copyright",
+            ]
+        );
+        assert_eq!(contents.files(), 3);
+
+        let usr = &contents.entries[0];
+        assert_eq!((usr.name(), usr.depth(), usr.size), ("usr", 0, 13));
+        assert_eq!(usr.permissions(), "drwxr-xr-x");
+
+        let hello = &contents.entries[2];
+        assert_eq!((hello.name(), hello.depth(), hello.size), ("hello", 2, 10));
+        assert!(hello.is_setuid());
+        assert_eq!(hello.permissions(), "-rwsr-xr-x");
+
+        let hi = &contents.entries[3];
+        assert_eq!(hi.kind, EntryKind::Symlink(String::from("hello")));
+        assert_eq!(hi.permissions(), "lrwxrwxrwx");
+        assert!(!hi.is_setuid());
+    }
+
+    #[test]
+    fn collapses_and_searches() {
+        let contents = contents();
+
+        let collapsed = HashSet::from([String::from("/usr/share")]);
+        assert_eq!(
+            paths(&contents.visible(&collapsed, "")),
+            [
+                "/usr",
+                "/usr/bin",
+                "/usr/bin/hello",
+                "/usr/bin/hi",
+                "/usr/share"
+            ]
+        );
+
+        let collapsed = HashSet::from([String::from("/usr")]);
+        assert_eq!(paths(&contents.visible(&collapsed, "")), ["/usr"]);
+        assert_eq!(
+            paths(&contents.visible(&collapsed, " COPY")),
+            ["/usr/share/doc/hello/copyright"]
+        );
+    }
+
+    #[test]
+    fn reads_package_file() {
+        let path = std::env::temp_dir().join(format!("wizard-contents-{}.deb", std::process::id()));
+        crate::deb::tests::build_deb(&path, crate::deb::tests::HELLO_CONTROL, ".xz");
+
+        let contents = Contents::read(&path).unwrap();
+        let all: Vec<&Entry> = contents.entries.iter().collect();
+        assert_eq!(paths(&all), ["/usr", "/usr/bin", "/usr/bin/hello"]);
+        assert_eq!(contents.entries[2].permissions(), "-rw-r--r--");
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn marks_existing_files() {
+        let root = std::env::temp_dir().join(format!("wizard-contents-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("usr/bin")).unwrap();
+        std::fs::write(root.join("usr/bin/hello"), b"").unwrap();
+        std::fs::create_dir_all(root.join("usr/share/doc/hello")).unwrap();
+        std::fs::write(root.join("usr/share/doc/hello/copyright"), b"").unwrap();
+
+        let mut contents = contents();
+        contents.mark_existing(&root, &HashSet::new());
+        let existing: Vec<&Entry> = contents.entries.iter().filter(|e| e.exists).collect();
+        assert_eq!(
+            paths(&existing),
+            ["/usr/bin/hello", "/usr/share/doc/hello/copyright"]
+        );
+        assert_eq!(contents.existing(), 2);
+
+        // What the installed version of the package put there is replaced, not overwritten.
+        let owned = HashSet::from([String::from("/usr/bin/hello")]);
+        contents.mark_existing(&root, &owned);
+        assert_eq!(contents.existing(), 1);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,356 @@
+//! Reading Debian binary packages directly, without going through PackageKit.
+//!
+//! https://manpages.debian.org/deb.5
+
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail};
+
+/// A `.deb` file whose control paragraph has been read.
+#[derive(Debug, Clone)]
+pub struct Deb {
+    path: PathBuf,
+    /// The format version from `debian-binary`, e.g. "2.0".
+    pub format_version: String,
+    pub control: Control,
+}
+
+impl Deb {
+    /// Reads `debian-binary` and the control paragraph from `control.tar.*`.
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let mut archive = ar::Archive::new(File::open(path)?);
+
+        let format_version = match archive.next_entry() {
+            Some(entry) => {
+                let mut entry = entry?;
+                if member_name(&entry) != "debian-binary" {
+                    bail!("not a Debian package: the first member is not debian-binary");
+                }
+                let mut version = String::new();
+                entry.read_to_string(&mut version)?;
+                version.trim().to_string()
+            }
+            None => bail!("not a Debian package: the archive is empty"),
+        };
+        if !format_version.starts_with("2.") {
+            bail!("unsupported package format version {format_version}");
+        }
+
+        while let Some(entry) = archive.next_entry() {
+            let entry = entry?;
+            let name = member_name(&entry);
+            if let Some(compression) = name.strip_prefix("control.tar") {
+                let control = read_control(decompress(compression, entry)?)?;
+                return Ok(Self {
+                    path: path.to_path_buf(),
+                    format_version,
+                    control,
+                });
+            }
+        }
+
+        bail!("not a Debian package: control.tar is missing")
+    }
+
+    /// Calls `f` with the decompressed `data.tar.*` stream, the files the package installs.
+    pub fn read_data<T>(
+        &self,
+        f: impl FnOnce(&mut dyn Read) -> anyhow::Result<T>,
+    ) -> anyhow::Result<T> {
+        let mut archive = ar::Archive::new(File::open(&self.path)?);
+
+        while let Some(entry) = archive.next_entry() {
+            let entry = entry?;
+            let name = member_name(&entry);
+            if let Some(compression) = name.strip_prefix("data.tar") {
+                return f(&mut decompress(compression, entry)?);
+            }
+        }
+
+        bail!("not a Debian package: data.tar is missing")
+    }
+}
+
+/// The name of an `ar` member, without the trailing slash some archivers add.
+fn member_name<R: Read>(entry: &ar::Entry<'_, R>) -> String {
+    String::from_utf8_lossy(entry.header().identifier())
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// Wraps a `*.tar<compression>` member in the matching decoder.
+fn decompress<'a>(compression: &str, member: impl Read + 'a) -> anyhow::Result<Box<dyn Read + 'a>> {
+    Ok(match compression {
+        "" => Box::new(member),
+        ".gz" => Box::new(flate2::read::GzDecoder::new(member)),
+        ".xz" => Box::new(xz2::read::XzDecoder::new(member)),
+        ".zst" => Box::new(zstd::stream::read::Decoder::new(member)?),
+        _ => bail!("unsupported compression tar{compression}"),
+    })
+}
+
+/// Finds the `control` file in the control archive and parses it.
+fn read_control(reader: impl Read) -> anyhow::Result<Control> {
+    let mut archive = tar::Archive::new(reader);
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?;
+        if path == Path::new("./control") || path == Path::new("control") {
+            let mut text = String::new();
+            entry.read_to_string(&mut text)?;
+            return Control::parse(&text);
+        }
+    }
+
+    Err(anyhow!("not a Debian package: the control file is missing"))
+}
+
+/// Fields whose lines each mean something; the lines of every other field are folded into one.
+const MULTILINE: &[&str] = &["Description", "Conffiles"];
+
+/// The fields of a binary package's control paragraph, in file order.
+///
+/// https://www.debian.org/doc/debian-policy/ch-controlfields.html#binary-package-control-files-debian-control
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Control {
+    fields: Vec<(String, String)>,
+}
+
+impl Control {
+    /// Parses a single deb822 paragraph.
+    ///
+    /// Continuation lines of multi-line fields like `Description` are kept with their
+    /// leading space removed, so they keep their line structure. Those of folded fields like
+    /// `Depends` are joined with a space.
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let mut fields: Vec<(String, String)> = Vec::new();
+
+        for line in text.lines() {
+            if line.trim().is_empty() {
+                if fields.is_empty() {
+                    continue;
+                }
+                break;
+            }
+            if line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with([' ', '\t']) {
+                let Some((name, value)) = fields.last_mut() else {
+                    bail!("continuation line before the first field: {line:?}");
+                };
+                if MULTILINE
+                    .iter()
+                    .any(|field| field.eq_ignore_ascii_case(name))
+                {
+                    value.push('\n');
+                    value.push_str(&line[1..]);
+                } else {
+                    if !value.is_empty() {
+                        value.push(' ');
+                    }
+                    value.push_str(line.trim());
+                }
+                continue;
+            }
+
+            let Some((name, value)) = line.split_once(':') else {
+                bail!("malformed control line: {line:?}");
+            };
+            fields.push((name.trim().to_string(), value.trim().to_string()));
+        }
+
+        if fields.is_empty() {
+            bail!("the control paragraph is empty");
+        }
+
+        Ok(Self { fields })
+    }
+
+    /// The value of a field, matched case-insensitively as deb822 requires.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// All fields, in the order they appear in the file.
+    #[cfg(test)]
+    pub fn fields(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.fields
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// The synopsis and the extended description.
+    ///
+    /// Lines consisting of a single `.` stand for empty lines.
+    pub fn description(&self) -> Option<(&str, String)> {
+        let description = self.get("Description")?;
+        let (synopsis, extended) = description.split_once('\n').unwrap_or((description, ""));
+        let extended = extended
+            .lines()
+            .map(|line| {
+                if line.trim() == "." {
+                    ""
+                } else {
+                    line.trim_start()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        Some((synopsis, extended))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    pub const HELLO_CONTROL: &str = "\
+Package: hello
+Version: 2.10-3
+Architecture: amd64
+Maintainer: Santiago Vila <sanvila@debian.org>
+Installed-Size: 280
+Depends: libc6 (>= 2.34)
+Conflicts: hello-traditional
+Breaks: hello-debhelper (<< 2.9)
+Replaces: hello-debhelper (<< 2.9), hello-traditional
+Section: devel
+Priority: optional
+Multi-Arch: foreign
+Homepage: https://www.gnu.org/software/hello/
+Description: example package based on GNU hello
+ The GNU hello program produces a familiar, friendly greeting.
+ .
+ Seriously, though: this is an example of how to do a Debian package.
+";
+
+    /// Writes a `.deb` with the given control file, compressing both tarballs with `compression`.
+    pub fn build_deb(path: &Path, control: &str, compression: &str) {
+        fn tarball(name: &str, contents: &[u8]) -> Vec<u8> {
+            let mut builder = tar::Builder::new(Vec::new());
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, contents).unwrap();
+            builder.into_inner().unwrap()
+        }
+
+        fn compress(compression: &str, tar: Vec<u8>) -> Vec<u8> {
+            use std::io::Write;
+            match compression {
+                "" => tar,
+                ".gz" => {
+                    let mut encoder =
+                        flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+                    encoder.write_all(&tar).unwrap();
+                    encoder.finish().unwrap()
+                }
+                ".xz" => {
+                    let mut encoder = xz2::write::XzEncoder::new(Vec::new(), 6);
+                    encoder.write_all(&tar).unwrap();
+                    encoder.finish().unwrap()
+                }
+                ".zst" => zstd::encode_all(tar.as_slice(), 3).unwrap(),
+                _ => unreachable!(),
+            }
+        }
+
+        let members = [
+            (String::from("debian-binary"), b"2.0\n".to_vec()),
+            (
+                format!("control.tar{compression}"),
+                compress(compression, tarball("./control", control.as_bytes())),
+            ),
+            (
+                format!("data.tar{compression}"),
+                compress(compression, tarball("./usr/bin/hello", b"#!/bin/sh\n")),
+            ),
+        ];
+
+        let mut builder = ar::Builder::new(File::create(path).unwrap());
+        for (name, data) in members {
+            let header = ar::Header::new(name.into_bytes(), data.len() as u64);
+            builder.append(&header, data.as_slice()).unwrap();
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("wizard-deb-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn reads_every_compression() {
+        for compression in ["", ".gz", ".xz", ".zst"] {
+            let path = temp_path(&format!("hello{compression}.deb"));
+            build_deb(&path, HELLO_CONTROL, compression);
+
+            let deb = Deb::open(&path).unwrap();
+            assert_eq!(deb.format_version, "2.0");
+            assert_eq!(deb.control.get("Package"), Some("hello"));
+            assert_eq!(deb.control.get("Multi-Arch"), Some("foreign"));
+
+            let files = deb
+                .read_data(|data| {
+                    let mut files = Vec::new();
+                    for entry in tar::Archive::new(data).entries()? {
+                        files.push(entry?.path()?.display().to_string());
+                    }
+                    Ok(files)
+                })
+                .unwrap();
+            assert_eq!(files, ["usr/bin/hello"]);
+
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        let path = temp_path("not-a-deb");
+        std::fs::write(&path, b"#!/bin/sh\n").unwrap();
+        assert!(Deb::open(&path).is_err());
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn parses_control_paragraph() {
+        let control = Control::parse(HELLO_CONTROL).unwrap();
+
+        assert_eq!(control.fields().count(), 14);
+        assert_eq!(control.get("depends"), Some("libc6 (>= 2.34)"));
+        assert_eq!(control.get("Installed-Size"), Some("280"));
+        assert_eq!(control.get("Pre-Depends"), None);
+
+        let (synopsis, extended) = control.description().unwrap();
+        assert_eq!(synopsis, "example package based on GNU hello");
+        assert_eq!(
+            extended,
+            "The GNU hello program produces a familiar, friendly greeting.\n\n\
+             Seriously, though: this is an example of how to do a Debian package."
+        );
+    }
+
+    #[test]
+    fn folds_continuation_lines() {
+        let control = Control::parse(
+            "Package: hello\nDepends: libc6 (>= 2.34),\n libgcc-s1\nConflicts:\n hello-traditional\n\
+             Version: 1\n",
+        )
+        .unwrap();
+        assert_eq!(control.get("Depends"), Some("libc6 (>= 2.34), libgcc-s1"));
+        assert_eq!(control.get("Conflicts"), Some("hello-traditional"));
+        assert_eq!(control.get("Version"), Some("1"));
+
+        assert!(Control::parse(" orphan\n").is_err());
+        assert!(Control::parse("no colon\n").is_err());
+    }
+}
@@ -0,0 +1,246 @@
+//! Reading dpkg's database of installed packages directly, without going through PackageKit.
+//!
+//! https://manpages.debian.org/dpkg.1#FILES
+
+use std::collections::HashSet;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context};
+
+use crate::deb::Control;
+use crate::packagekit::PackageId;
+
+/// Where dpkg records the state of every package it knows about.
+pub const STATUS: &str = "/var/lib/dpkg/status";
+
+/// Where dpkg lists the files of each installed package.
+pub const INFO: &str = "/var/lib/dpkg/info";
+
+/// The paths dpkg installed for a package, read from its list in `info`.
+///
+/// The list is `<name>:<arch>.list` for packages that can be installed for several
+/// architectures and `<name>.list` otherwise. Packages that are not installed have none.
+pub fn files(info: impl AsRef<Path>, name: &str, arch: &str) -> anyhow::Result<HashSet<String>> {
+    let info = info.as_ref();
+    for list in [format!("{name}:{arch}.list"), format!("{name}.list")] {
+        let path = info.join(list);
+        match std::fs::read_to_string(&path) {
+            Ok(text) => return Ok(text.lines().map(str::to_string).collect()),
+            Err(why) if why.kind() == ErrorKind::NotFound => continue,
+            Err(why) => {
+                return Err(why).with_context(|| format!("failed to read {}", path.display()))
+            }
+        }
+    }
+    Ok(HashSet::new())
+}
+
+/// A configuration file shipped by a package, as listed in its `Conffiles` field.
+#[allow(dead_code)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Conffile {
+    pub path: String,
+    /// The MD5 sum of the file as shipped, which tells whether the user changed it.
+    pub md5: String,
+    /// Whether the package no longer ships the file.
+    pub obsolete: bool,
+}
+
+/// A package paragraph of the status file.
+#[allow(dead_code)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InstalledPackage {
+    pub name: String,
+    pub version: String,
+    pub arch: String,
+    /// The last word of the `Status` field, e.g. `installed` or `config-files`.
+    pub state: String,
+    pub provides: String,
+    pub conflicts: String,
+    pub breaks: String,
+    pub replaces: String,
+    pub conffiles: Vec<Conffile>,
+}
+
+impl InstalledPackage {
+    fn new(control: &Control) -> anyhow::Result<Self> {
+        let field = |name| control.get(name).unwrap_or_default().to_string();
+        let Some(name) = control.get("Package") else {
+            bail!("paragraph without a Package field");
+        };
+        let state = field("Status")
+            .split_whitespace()
+            .last()
+            .unwrap_or_default()
+            .to_string();
+
+        let conffiles = field("Conffiles")
+            .lines()
+            .filter_map(|line| {
+                let mut words = line.split_whitespace();
+                let (path, md5) = (words.next()?, words.next()?);
+                Some(Conffile {
+                    path: path.to_string(),
+                    md5: md5.to_string(),
+                    obsolete: words.any(|word| word == "obsolete"),
+                })
+            })
+            .collect();
+
+        Ok(Self {
+            name: name.to_string(),
+            version: field("Version"),
+            arch: field("Architecture"),
+            state,
+            provides: field("Provides"),
+            conflicts: field("Conflicts"),
+            breaks: field("Breaks"),
+            replaces: field("Replaces"),
+            conffiles,
+        })
+    }
+
+    /// Whether the package is fully installed, rather than removed or half-installed.
+    pub fn is_installed(&self) -> bool {
+        self.state == "installed"
+    }
+
+    /// The package ID PackageKit would report for this package.
+    pub fn id(&self) -> PackageId {
+        PackageId {
+            name: self.name.clone(),
+            version: self.version.clone(),
+            arch: self.arch.clone(),
+            data: String::from("installed"),
+        }
+    }
+}
+
+/// The packages recorded in a dpkg status file, in file order.
+#[derive(Clone, Debug, Default)]
+pub struct Database {
+    pub packages: Vec<InstalledPackage>,
+}
+
+impl Database {
+    /// Reads a status file, or the `-old` copy dpkg keeps next to it if that fails.
+    pub fn read(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let error = match Self::read_file(path) {
+            Ok(database) => return Ok(database),
+            Err(why) => why,
+        };
+
+        let mut old = path.as_os_str().to_owned();
+        old.push("-old");
+        let old = PathBuf::from(old);
+        Self::read_file(&old).map_err(|_| error)
+    }
+
+    fn read_file(path: &Path) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("failed to parse {}", path.display()))
+    }
+
+    /// Parses the paragraphs of a status file.
+    ///
+    /// Packages dpkg only remembers as purged have no version and are skipped.
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let mut packages = Vec::new();
+        for paragraph in text.split("\n\n").filter(|p| !p.trim().is_empty()) {
+            let package = InstalledPackage::new(&Control::parse(paragraph)?)?;
+            if !package.version.is_empty() {
+                packages.push(package);
+            }
+        }
+        Ok(Self { packages })
+    }
+
+    /// The fully installed packages.
+    pub fn installed(&self) -> impl Iterator<Item = &InstalledPackage> {
+        self.packages
+            .iter()
+            .filter(|package| package.is_installed())
+    }
+
+    /// The installed package with this name that a package for `arch` would replace.
+    ///
+    /// Packages for `all` match any architecture, as dpkg allows only one of them.
+    pub fn find(&self, name: &str, arch: &str) -> Option<&InstalledPackage> {
+        self.installed().find(|package| {
+            package.name == name && (package.arch == arch || package.arch == "all" || arch == "all")
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/dpkg");
+
+    #[test]
+    fn reads_status_file() {
+        let database = Database::read(Path::new(FIXTURES).join("status")).unwrap();
+
+        // `sl` was purged and has no version.
+        assert_eq!(database.packages.len(), 4);
+        assert_eq!(database.installed().count(), 3);
+
+        let libc6 = database.find("libc6", "i386").unwrap();
+        assert_eq!(libc6.version, "2.36-9+deb12u4");
+        assert_eq!(libc6.breaks, "");
+        assert_eq!(
+            database.find("libc6", "amd64").unwrap().conffiles,
+            [Conffile {
+                path: String::from("/etc/ld.so.conf.d/x86_64-linux-gnu.conf"),
+                md5: String::from("d4e7a7b88a71b5ffd9e2644e71a0cfab"),
+                obsolete: false,
+            }]
+        );
+
+        let postfix = database.find("postfix", "all").unwrap();
+        assert_eq!(postfix.provides, "default-mta, mail-transport-agent");
+        assert_eq!(postfix.conflicts, "mail-transport-agent, smail");
+        assert_eq!(postfix.replaces, "mail-transport-agent");
+        assert_eq!(postfix.conffiles.len(), 3);
+        assert!(postfix.conffiles[2].obsolete);
+        assert_eq!(
+            postfix.id().to_string(),
+            "postfix;3.7.10-0+deb12u1;amd64;installed"
+        );
+
+        // Removed packages keep their configuration files, but are not installed.
+        assert!(database.find("hello-traditional", "amd64").is_none());
+        assert_eq!(database.packages[3].state, "config-files");
+    }
+
+    #[test]
+    fn reads_package_files() {
+        let hello = files(FIXTURES, "hello", "amd64").unwrap();
+        assert!(hello.contains("/usr/bin/hello"));
+        assert_eq!(hello.len(), 8);
+
+        // Packages for several architectures have the architecture in the name of the list.
+        let libc6 = files(FIXTURES, "libc6", "amd64").unwrap();
+        assert!(libc6.contains("/usr/lib/x86_64-linux-gnu/libc.so.6"));
+        assert!(files(FIXTURES, "sl", "amd64").unwrap().is_empty());
+    }
+
+    #[test]
+    fn falls_back_to_old_status() {
+        let database = Database::read(Path::new(FIXTURES).join("old/status")).unwrap();
+        assert_eq!(database.find("hello", "amd64").unwrap().version, "2.10-3");
+
+        assert!(Database::read(Path::new(FIXTURES).join("missing/status")).is_err());
+    }
+
+    #[test]
+    fn rejects_broken_paragraphs() {
+        assert!(Database::parse("Version: 1.0\n").is_err());
+        assert!(Database::parse("Package: hello\nnot a field\n").is_err());
+        assert!(Database::parse("").unwrap().packages.is_empty());
+    }
+}
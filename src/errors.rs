@@ -0,0 +1,154 @@
+//! Human explanations of the errors PackageKit reports.
+
+use crate::fl;
+use crate::packagekit::Error;
+
+/// A localized explanation of what went wrong.
+pub fn explanation(code: Error) -> String {
+    match code {
+        Error::Unknown => fl!("error-unknown"),
+        Error::Oom => fl!("error-out-of-memory"),
+        Error::NoNetwork => fl!("error-no-network"),
+        Error::NotSupported => fl!("error-not-supported"),
+        Error::InternalError => fl!("error-internal-error"),
+        Error::GpgFailure => fl!("error-gpg-failure"),
+        Error::PackageIdInvalid => fl!("error-package-id-invalid"),
+        Error::PackageNotInstalled => fl!("error-package-not-installed"),
+        Error::PackageNotFound => fl!("error-package-not-found"),
+        Error::PackageAlreadyInstalled => fl!("error-package-already-installed"),
+        Error::PackageDownloadFailed => fl!("error-package-download-failed"),
+        Error::GroupNotFound => fl!("error-group-not-found"),
+        Error::GroupListInvalid => fl!("error-group-list-invalid"),
+        Error::DepResolutionFailed => fl!("error-dep-resolution-failed"),
+        Error::FilterInvalid => fl!("error-filter-invalid"),
+        Error::CreateThreadFailed => fl!("error-create-thread-failed"),
+        Error::TransactionError => fl!("error-transaction-error"),
+        Error::TransactionCancelled => fl!("error-transaction-cancelled"),
+        Error::NoCache => fl!("error-no-cache"),
+        Error::RepoNotFound => fl!("error-repo-not-found"),
+        Error::CannotRemoveSystemPackage => fl!("error-cannot-remove-system-package"),
+        Error::ProcessKill => fl!("error-process-kill"),
+        Error::FailedInitialization => fl!("error-failed-initialization"),
+        Error::FailedFinalise => fl!("error-failed-finalise"),
+        Error::FailedConfigParsing => fl!("error-failed-config-parsing"),
+        Error::CannotCancel => fl!("error-cannot-cancel"),
+        Error::CannotGetLock => fl!("error-cannot-get-lock"),
+        Error::NoPackagesToUpdate => fl!("error-no-packages-to-update"),
+        Error::CannotWriteRepoConfig => fl!("error-cannot-write-repo-config"),
+        Error::LocalInstallFailed => fl!("error-local-install-failed"),
+        Error::BadGpgSignature => fl!("error-bad-gpg-signature"),
+        Error::MissingGpgSignature => fl!("error-missing-gpg-signature"),
+        Error::CannotInstallSourcePackage => fl!("error-cannot-install-source-package"),
+        Error::RepoConfigurationError => fl!("error-repo-configuration-error"),
+        Error::NoLicenseAgreement => fl!("error-no-license-agreement"),
+        Error::FileConflicts => fl!("error-file-conflicts"),
+        Error::PackageConflicts => fl!("error-package-conflicts"),
+        Error::RepoNotAvailable => fl!("error-repo-not-available"),
+        Error::InvalidPackageFile => fl!("error-invalid-package-file"),
+        Error::PackageInstallBlocked => fl!("error-package-install-blocked"),
+        Error::PackageCorrupt => fl!("error-package-corrupt"),
+        Error::AllPackagesAlreadyInstalled => fl!("error-all-packages-already-installed"),
+        Error::FileNotFound => fl!("error-file-not-found"),
+        Error::NoMoreMirrorsToTry => fl!("error-no-more-mirrors-to-try"),
+        Error::NoDistroUpgradeData => fl!("error-no-distro-upgrade-data"),
+        Error::IncompatibleArchitecture => fl!("error-incompatible-architecture"),
+        Error::NoSpaceOnDevice => fl!("error-no-space-on-device"),
+        Error::MediaChangeRequired => fl!("error-media-change-required"),
+        Error::NotAuthorized => fl!("error-not-authorized"),
+        Error::UpdateNotFound => fl!("error-update-not-found"),
+        Error::CannotInstallRepoUnsigned => fl!("error-cannot-install-repo-unsigned"),
+        Error::CannotUpdateRepoUnsigned => fl!("error-cannot-update-repo-unsigned"),
+        Error::CannotGetFilelist => fl!("error-cannot-get-filelist"),
+        Error::CannotGetRequires => fl!("error-cannot-get-requires"),
+        Error::CannotDisableRepository => fl!("error-cannot-disable-repository"),
+        Error::RestrictedDownload => fl!("error-restricted-download"),
+        Error::PackageFailedToConfigure => fl!("error-package-failed-to-configure"),
+        Error::PackageFailedToBuild => fl!("error-package-failed-to-build"),
+        Error::PackageFailedToInstall => fl!("error-package-failed-to-install"),
+        Error::PackageFailedToRemove => fl!("error-package-failed-to-remove"),
+        Error::UpdateFailedDueToRunningProcess => fl!("error-failed-due-to-running-process"),
+        Error::PackageDatabaseChanged => fl!("error-package-database-changed"),
+        Error::ProvideTypeNotSupported => fl!("error-provide-type-not-supported"),
+        Error::InstallRootInvalid => fl!("error-install-root-invalid"),
+        Error::CannotFetchSources => fl!("error-cannot-fetch-sources"),
+        Error::CancelledPriority => fl!("error-cancelled-priority"),
+        Error::UnfinishedTransaction => fl!("error-unfinished-transaction"),
+        Error::LockRequired => fl!("error-lock-required"),
+        Error::RepoAlreadySet => fl!("error-repo-already-set"),
+    }
+}
+
+/// A localized suggestion of what the user can do about it.
+pub fn suggestion(code: Error) -> String {
+    match code {
+        Error::Unknown => fl!("error-unknown-action"),
+        Error::Oom => fl!("error-out-of-memory-action"),
+        Error::NoNetwork => fl!("error-no-network-action"),
+        Error::NotSupported => fl!("error-not-supported-action"),
+        Error::InternalError => fl!("error-internal-error-action"),
+        Error::GpgFailure => fl!("error-gpg-failure-action"),
+        Error::PackageIdInvalid => fl!("error-package-id-invalid-action"),
+        Error::PackageNotInstalled => fl!("error-package-not-installed-action"),
+        Error::PackageNotFound => fl!("error-package-not-found-action"),
+        Error::PackageAlreadyInstalled => fl!("error-package-already-installed-action"),
+        Error::PackageDownloadFailed => fl!("error-package-download-failed-action"),
+        Error::GroupNotFound => fl!("error-group-not-found-action"),
+        Error::GroupListInvalid => fl!("error-group-list-invalid-action"),
+        Error::DepResolutionFailed => fl!("error-dep-resolution-failed-action"),
+        Error::FilterInvalid => fl!("error-filter-invalid-action"),
+        Error::CreateThreadFailed => fl!("error-create-thread-failed-action"),
+        Error::TransactionError => fl!("error-transaction-error-action"),
+        Error::TransactionCancelled => fl!("error-transaction-cancelled-action"),
+        Error::NoCache => fl!("error-no-cache-action"),
+        Error::RepoNotFound => fl!("error-repo-not-found-action"),
+        Error::CannotRemoveSystemPackage => fl!("error-cannot-remove-system-package-action"),
+        Error::ProcessKill => fl!("error-process-kill-action"),
+        Error::FailedInitialization => fl!("error-failed-initialization-action"),
+        Error::FailedFinalise => fl!("error-failed-finalise-action"),
+        Error::FailedConfigParsing => fl!("error-failed-config-parsing-action"),
+        Error::CannotCancel => fl!("error-cannot-cancel-action"),
+        Error::CannotGetLock => fl!("error-cannot-get-lock-action"),
+        Error::NoPackagesToUpdate => fl!("error-no-packages-to-update-action"),
+        Error::CannotWriteRepoConfig => fl!("error-cannot-write-repo-config-action"),
+        Error::LocalInstallFailed => fl!("error-local-install-failed-action"),
+        Error::BadGpgSignature => fl!("error-bad-gpg-signature-action"),
+        Error::MissingGpgSignature => fl!("error-missing-gpg-signature-action"),
+        Error::CannotInstallSourcePackage => fl!("error-cannot-install-source-package-action"),
+        Error::RepoConfigurationError => fl!("error-repo-configuration-error-action"),
+        Error::NoLicenseAgreement => fl!("error-no-license-agreement-action"),
+        Error::FileConflicts => fl!("error-file-conflicts-action"),
+        Error::PackageConflicts => fl!("error-package-conflicts-action"),
+        Error::RepoNotAvailable => fl!("error-repo-not-available-action"),
+        Error::InvalidPackageFile => fl!("error-invalid-package-file-action"),
+        Error::PackageInstallBlocked => fl!("error-package-install-blocked-action"),
+        Error::PackageCorrupt => fl!("error-package-corrupt-action"),
+        Error::AllPackagesAlreadyInstalled => fl!("error-all-packages-already-installed-action"),
+        Error::FileNotFound => fl!("error-file-not-found-action"),
+        Error::NoMoreMirrorsToTry => fl!("error-no-more-mirrors-to-try-action"),
+        Error::NoDistroUpgradeData => fl!("error-no-distro-upgrade-data-action"),
+        Error::IncompatibleArchitecture => fl!("error-incompatible-architecture-action"),
+        Error::NoSpaceOnDevice => fl!("error-no-space-on-device-action"),
+        Error::MediaChangeRequired => fl!("error-media-change-required-action"),
+        Error::NotAuthorized => fl!("error-not-authorized-action"),
+        Error::UpdateNotFound => fl!("error-update-not-found-action"),
+        Error::CannotInstallRepoUnsigned => fl!("error-cannot-install-repo-unsigned-action"),
+        Error::CannotUpdateRepoUnsigned => fl!("error-cannot-update-repo-unsigned-action"),
+        Error::CannotGetFilelist => fl!("error-cannot-get-filelist-action"),
+        Error::CannotGetRequires => fl!("error-cannot-get-requires-action"),
+        Error::CannotDisableRepository => fl!("error-cannot-disable-repository-action"),
+        Error::RestrictedDownload => fl!("error-restricted-download-action"),
+        Error::PackageFailedToConfigure => fl!("error-package-failed-to-configure-action"),
+        Error::PackageFailedToBuild => fl!("error-package-failed-to-build-action"),
+        Error::PackageFailedToInstall => fl!("error-package-failed-to-install-action"),
+        Error::PackageFailedToRemove => fl!("error-package-failed-to-remove-action"),
+        Error::UpdateFailedDueToRunningProcess => fl!("error-failed-due-to-running-process-action"),
+        Error::PackageDatabaseChanged => fl!("error-package-database-changed-action"),
+        Error::ProvideTypeNotSupported => fl!("error-provide-type-not-supported-action"),
+        Error::InstallRootInvalid => fl!("error-install-root-invalid-action"),
+        Error::CannotFetchSources => fl!("error-cannot-fetch-sources-action"),
+        Error::CancelledPriority => fl!("error-cancelled-priority-action"),
+        Error::UnfinishedTransaction => fl!("error-unfinished-transaction-action"),
+        Error::LockRequired => fl!("error-lock-required-action"),
+        Error::RepoAlreadySet => fl!("error-repo-already-set-action"),
+    }
+}
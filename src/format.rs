@@ -0,0 +1,144 @@
+//! Formatting of sizes, speeds and durations for display.
+
+use once_cell::sync::Lazy;
+
+use crate::fl;
+
+/// Languages that write decimals with a comma, from the CLDR number symbols.
+const COMMA_LANGUAGES: &[&str] = &[
+    "af", "az", "be", "bg", "bs", "ca", "cs", "da", "de", "el", "es", "et", "eu", "fi", "fo", "fr",
+    "fy", "gl", "hr", "hu", "hy", "id", "is", "it", "ka", "kk", "ky", "lb", "lt", "lv", "mk", "mn",
+    "nb", "nl", "nn", "no", "pl", "pt", "ro", "ru", "sk", "sl", "sq", "sr", "sv", "tr", "uk", "uz",
+    "vi",
+];
+
+/// Regions where those languages use a point after all.
+const POINT_REGIONS: &[(&str, &str)] = &[
+    ("de", "CH"),
+    ("de", "LI"),
+    ("es", "MX"),
+    ("es", "US"),
+    ("it", "CH"),
+];
+
+/// The decimal separator of the locale numbers are formatted in.
+static DECIMAL_SEPARATOR: Lazy<char> = Lazy::new(|| {
+    let locale = ["LC_ALL", "LC_NUMERIC", "LANG"]
+        .into_iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|locale| !locale.is_empty())
+        .unwrap_or_default();
+    decimal_separator(&locale)
+});
+
+/// The decimal separator of a POSIX locale name such as `de_DE.UTF-8`.
+fn decimal_separator(locale: &str) -> char {
+    let locale = locale.split(['.', '@']).next().unwrap_or_default();
+    let (language, region) = locale.split_once('_').unwrap_or((locale, ""));
+    if COMMA_LANGUAGES.contains(&language) && !POINT_REGIONS.contains(&(language, region)) {
+        ','
+    } else {
+        '.'
+    }
+}
+
+/// Formats a size in bytes with SI units, e.g. "900 kB" or "1.2 MB".
+pub fn format_size(bytes: u64) -> String {
+    size_text(bytes, *DECIMAL_SEPARATOR)
+}
+
+/// Formats a size with the given decimal separator.
+///
+/// The size is rounded before its unit is chosen, so 999,999 bytes are "1.0 MB" rather
+/// than "1000 kB".
+fn size_text(bytes: u64, separator: char) -> String {
+    if bytes < 1_000 {
+        return fl!("size-bytes", size = bytes);
+    }
+
+    let value = bytes as f64;
+    let (kb, mb, gb) = (value / 1e3, value / 1e6, value / 1e9);
+    if let Some(size) = rounded(kb, separator) {
+        fl!("size-kb", size = size)
+    } else if let Some(size) = rounded(mb, separator) {
+        fl!("size-mb", size = size)
+    } else {
+        let size = rounded(gb, separator).unwrap_or_else(|| format!("{gb:.0}"));
+        fl!("size-gb", size = size)
+    }
+}
+
+/// One decimal below 10, none above, or `None` if it rounds to 1000 or more.
+fn rounded(value: f64, separator: char) -> Option<String> {
+    let tenths = (value * 10.0).round() / 10.0;
+    let text = if tenths < 10.0 {
+        format!("{tenths:.1}").replace('.', &separator.to_string())
+    } else if value.round() < 1000.0 {
+        format!("{:.0}", value.round())
+    } else {
+        return None;
+    };
+    Some(text)
+}
+
+/// Formats a download speed, e.g. "1.2 MB/s".
+pub fn format_speed(bytes_per_second: u32) -> String {
+    fl!("speed", size = format_size(u64::from(bytes_per_second)))
+}
+
+/// Formats a number of seconds as "m:ss", or "h:mm:ss" for an hour or more.
+pub fn format_duration(seconds: u32) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{hours}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{minutes}:{seconds:02}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes() {
+        assert_eq!(size_text(512, '.'), fl!("size-bytes", size = 512));
+        assert_eq!(size_text(900_000, '.'), fl!("size-kb", size = "900"));
+        assert_eq!(size_text(1_234_567, '.'), fl!("size-mb", size = "1.2"));
+        assert_eq!(size_text(1_234_567, ','), fl!("size-mb", size = "1,2"));
+        assert_eq!(size_text(57_348_000, '.'), fl!("size-mb", size = "57"));
+        assert_eq!(size_text(2_500_000_000, '.'), fl!("size-gb", size = "2.5"));
+        assert_eq!(
+            size_text(2_500_000_000_000, '.'),
+            fl!("size-gb", size = "2500")
+        );
+    }
+
+    #[test]
+    fn sizes_are_rounded_before_the_unit_is_chosen() {
+        assert_eq!(size_text(999, '.'), fl!("size-bytes", size = 999));
+        assert_eq!(size_text(999_499, '.'), fl!("size-kb", size = "999"));
+        assert_eq!(size_text(999_500, '.'), fl!("size-mb", size = "1.0"));
+        assert_eq!(size_text(999_999, '.'), fl!("size-mb", size = "1.0"));
+        assert_eq!(size_text(9_949_999, '.'), fl!("size-mb", size = "9.9"));
+        assert_eq!(size_text(9_960_000, '.'), fl!("size-mb", size = "10"));
+        assert_eq!(size_text(999_999_999, '.'), fl!("size-gb", size = "1.0"));
+    }
+
+    #[test]
+    fn decimal_separators() {
+        assert_eq!(decimal_separator("de_DE.UTF-8"), ',');
+        assert_eq!(decimal_separator("de_CH.UTF-8"), '.');
+        assert_eq!(decimal_separator("pt_BR.UTF-8@euro"), ',');
+        assert_eq!(decimal_separator("en_US.UTF-8"), '.');
+        assert_eq!(decimal_separator("C"), '.');
+        assert_eq!(decimal_separator(""), '.');
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(format_duration(0), "0:00");
+        assert_eq!(format_duration(75), "1:15");
+        assert_eq!(format_duration(3_725), "1:02:05");
+    }
+}
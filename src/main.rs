@@ -1,10 +1,25 @@
 // SPDX-License-Identifier: {{LICENSE}}
 
 mod app;
+mod arch;
+mod backend;
 mod config;
+mod conflict;
+mod contents;
+mod deb;
+mod dpkg;
+mod errors;
+mod format;
 mod i18n;
 mod package;
 mod packagekit;
+mod polkit;
+mod preview;
+mod progress;
+mod relation;
+mod selection;
+mod unmet;
+mod version;
 
 fn main() -> cosmic::iced::Result {
     // Get the system's preferred languages.
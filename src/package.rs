@@ -1,55 +1,824 @@
-use crate::packagekit::{PackageKit, TransactionDetails};
+use std::cmp::Ordering;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{DefaultHasher, Hasher};
+use std::io::Read;
+use std::sync::Arc;
+
+use anyhow::Context;
+use futures_util::future::BoxFuture;
+use futures_util::{stream, StreamExt};
+use tokio::sync::{Mutex, Semaphore};
+
+use crate::backend::PackageBackend;
+use crate::deb::{Control, Deb};
+use crate::dpkg::{self, Database, InstalledPackage};
+use crate::packagekit::{
+    EventStream, Exit, Filter, PackageId, TransactionDetails, TransactionError, TransactionEvent,
+    TransactionFlag,
+};
+use crate::version;
 
 #[derive(Debug, Clone)]
 pub struct Package {
     pub path: String,
-    pub id: String,
-    pub name: String,
-    pub version: String,
-    pub architecture: String,
+    pub id: PackageId,
     pub summary: String,
     pub description: String,
     pub url: String,
     pub license: String,
-    pub size: String,
+    /// The size of the installed package in bytes, as PackageKit reports it.
+    pub size: u64,
+    pub maintainer: String,
+    pub section: String,
+    pub priority: String,
+    /// The disk space the package uses once installed, in bytes.
+    pub installed_size: Option<u64>,
+    pub homepage: String,
+    pub multi_arch: String,
+    pub depends: String,
+    pub pre_depends: String,
+    pub recommends: String,
+    pub conflicts: String,
+    pub breaks: String,
+    pub replaces: String,
+    pub provides: String,
+    /// The version of this package already on the system, if any.
+    pub installed: Option<PackageId>,
+    /// The same or a newer version of this package in the repositories, if any.
+    pub available: Option<PackageId>,
+    /// Whether the user chose to install [`Package::available`] instead of the file.
+    pub use_repository: bool,
 }
 
-impl Package {
-    pub fn new(path: String, tx: TransactionDetails) -> Self {
-        let mut parts = tx.package_id.split(';');
-        let package_name = parts.next().unwrap_or("");
-        let version = parts.next().unwrap_or("");
-        let architecture = parts.next().unwrap_or("");
+/// What installing a package does, given what is already installed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Install,
+    Upgrade,
+    Reinstall,
+    Downgrade,
+}
 
-        Self {
+impl Action {
+    /// The transaction flag PackageKit needs before it agrees to this action.
+    pub fn flag(self) -> Option<TransactionFlag> {
+        match self {
+            Action::Install | Action::Upgrade => None,
+            Action::Reinstall => Some(TransactionFlag::AllowReinstall),
+            Action::Downgrade => Some(TransactionFlag::AllowDowngrade),
+        }
+    }
+}
+
+impl Package {
+    pub fn new(path: String, tx: TransactionDetails) -> anyhow::Result<Self> {
+        Ok(Self {
             path,
-            id: tx.package_id.clone(),
-            name: package_name.to_string(),
-            version: version.to_string(),
-            architecture: architecture.to_string(),
+            id: tx.package_id.parse()?,
             summary: tx.summary,
             description: tx.description,
             url: tx.url,
             license: tx.license,
             size: tx.size,
+            maintainer: String::new(),
+            section: String::new(),
+            priority: String::new(),
+            installed_size: None,
+            homepage: String::new(),
+            multi_arch: String::new(),
+            depends: String::new(),
+            pre_depends: String::new(),
+            recommends: String::new(),
+            conflicts: String::new(),
+            breaks: String::new(),
+            replaces: String::new(),
+            provides: String::new(),
+            installed: None,
+            available: None,
+            use_repository: false,
+        })
+    }
+
+    /// Reads a package from its control file alone, for when PackageKit cannot.
+    pub fn from_control(path: String, deb: &Deb) -> anyhow::Result<Self> {
+        let field = |name| {
+            deb.control
+                .get(name)
+                .map(str::to_string)
+                .with_context(|| format!("the control file has no {name} field"))
+        };
+        let id = PackageId {
+            name: field("Package")?,
+            version: field("Version")?,
+            arch: field("Architecture")?,
+            data: String::from("local"),
+        };
+        let details = TransactionDetails {
+            package_id: id.to_string(),
+            summary: String::new(),
+            description: String::new(),
+            url: String::new(),
+            license: String::new(),
+            size: 0,
+            download_size: 0,
+        };
+        let mut package = Self::new(path, details)?.with_control(&deb.control);
+        package.size = package.installed_size.unwrap_or_default();
+        Ok(package)
+    }
+
+    /// The repository package to install instead of the file, if the user chose one.
+    pub fn repository_choice(&self) -> Option<&PackageId> {
+        self.available.as_ref().filter(|_| self.use_repository)
+    }
+
+    /// Compares this package with the installed version.
+    ///
+    /// Versions dpkg cannot parse are only told apart by whether they are identical.
+    pub fn action(&self) -> Action {
+        let Some(installed) = &self.installed else {
+            return Action::Install;
+        };
+        let ordering = version::compare(&self.id.version, &installed.version).unwrap_or(
+            if self.id.version == installed.version {
+                Ordering::Equal
+            } else {
+                Ordering::Greater
+            },
+        );
+        match ordering {
+            Ordering::Greater => Action::Upgrade,
+            Ordering::Equal => Action::Reinstall,
+            Ordering::Less => Action::Downgrade,
+        }
+    }
+
+    /// Whether an installed package is the same package, for this one's architecture.
+    fn is_installed_as(&self, installed: &PackageId) -> bool {
+        installed.name == self.id.name
+            && (installed.arch == self.id.arch || installed.arch == "all" || self.id.arch == "all")
+    }
+
+    /// Fills in what PackageKit does not report from the package's own control file.
+    pub fn with_control(mut self, control: &Control) -> Self {
+        let field = |name| control.get(name).unwrap_or_default().to_string();
+
+        if let Some((synopsis, extended)) = control.description() {
+            if self.summary.is_empty() {
+                self.summary = synopsis.to_string();
+            }
+            if self.description.is_empty() {
+                self.description = extended;
+            }
+        }
+        if self.url.is_empty() {
+            self.url = field("Homepage");
+        }
+
+        self.maintainer = field("Maintainer");
+        self.section = field("Section");
+        self.priority = field("Priority");
+        // Installed-Size is in kibibytes.
+        self.installed_size = control
+            .get("Installed-Size")
+            .and_then(|size| size.parse::<u64>().ok())
+            .map(|size| size * 1024);
+        self.homepage = field("Homepage");
+        self.multi_arch = field("Multi-Arch");
+        self.depends = field("Depends");
+        self.pre_depends = field("Pre-Depends");
+        self.recommends = field("Recommends");
+        self.conflicts = field("Conflicts");
+        self.breaks = field("Breaks");
+        self.replaces = field("Replaces");
+        self.provides = field("Provides");
+        self
+    }
+}
+
+/// A file added by the user, and what is known about its contents so far.
+#[derive(Debug, Clone)]
+pub struct PackageFile {
+    pub path: String,
+    pub state: FileState,
+}
+
+#[derive(Debug, Clone)]
+pub enum FileState {
+    Loading,
+    Loaded(Vec<Package>),
+    Failed(TransactionError),
+}
+
+impl PackageFile {
+    /// A file whose details are being loaded.
+    pub fn new(path: String) -> Self {
+        Self {
+            path,
+            state: FileState::Loading,
+        }
+    }
+
+    /// The name of the file, without its directory.
+    pub fn file_name(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+
+    /// The packages read from the file, or none if it is not loaded.
+    pub fn packages(&self) -> &[Package] {
+        match &self.state {
+            FileState::Loaded(packages) => packages,
+            FileState::Loading | FileState::Failed(_) => &[],
+        }
+    }
+}
+
+/// How many files have their details read at the same time.
+const MAX_CONCURRENT_LOADS: usize = 4;
+
+/// What was read from a file: PackageKit's details, and the file itself if it could be parsed.
+type CachedDetails = (Vec<TransactionDetails>, Option<Deb>);
+
+/// Reads the details of added files in the background, a few at a time.
+///
+/// Details are cached by the contents of the file, so adding the same file again,
+/// even from another location, does not start a new transaction.
+#[derive(Debug, Clone)]
+pub struct DetailsLoader {
+    permits: Arc<Semaphore>,
+    cache: Arc<Mutex<HashMap<u64, CachedDetails>>>,
+}
+
+impl Default for DetailsLoader {
+    fn default() -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(MAX_CONCURRENT_LOADS)),
+            cache: Arc::default(),
+        }
+    }
+}
+
+impl DetailsLoader {
+    /// Reads the packages contained in a local file, waiting for a free slot first.
+    ///
+    /// When PackageKit cannot read the file, its control file still tells what it is. Only
+    /// PackageKit's details are cached, so it is asked again for the next file.
+    ///
+    /// `status` is dpkg's database if the app has read it already, which tells what is
+    /// installed when PackageKit cannot.
+    pub async fn load(
+        &self,
+        backend: Arc<dyn PackageBackend>,
+        path: String,
+        status: Option<Arc<Database>>,
+    ) -> anyhow::Result<Vec<Package>> {
+        let _permit = self.permits.acquire().await?;
+
+        let hash = hash_file(path.clone()).await?;
+        let cached = self.cache.lock().await.get(&hash).cloned();
+        let mut packages = match cached {
+            Some((tx_details, deb)) => from_details(&path, tx_details, deb.as_ref())?,
+            None => {
+                let deb = read_deb(path.clone()).await;
+                match backend.details(vec![path.clone()]).await {
+                    Ok(tx_details) => {
+                        let cached = (tx_details.clone(), deb.clone());
+                        self.cache.lock().await.insert(hash, cached);
+                        from_details(&path, tx_details, deb.as_ref())?
+                    }
+                    Err(why) => {
+                        let Some(deb) = deb else {
+                            return Err(why);
+                        };
+                        eprintln!("failed to get details, using the control file: {why:#}");
+                        vec![Package::from_control(path.clone(), &deb)?]
+                    }
+                }
+            }
+        };
+
+        // The installed versions change with every install, so they are never cached.
+        if let Err(why) = resolve_installed(&*backend, &mut packages).await {
+            eprintln!("failed to resolve installed packages: {why:#}");
+            // dpkg's own records still tell what is installed.
+            if let Err(why) = installed_from_status(status, &mut packages).await {
+                eprintln!("failed to read installed packages from dpkg: {why:#}");
+            }
+        }
+        if let Err(why) = resolve_available(&*backend, &mut packages).await {
+            eprintln!("failed to resolve available packages: {why:#}");
+        }
+
+        Ok(packages)
+    }
+}
+
+/// The packages PackageKit found in a file, completed from its control file if it was read.
+fn from_details(
+    path: &str,
+    tx_details: Vec<TransactionDetails>,
+    deb: Option<&Deb>,
+) -> anyhow::Result<Vec<Package>> {
+    tx_details
+        .into_iter()
+        .map(|tx_detail| {
+            let package = Package::new(path.to_string(), tx_detail)?;
+            Ok(match deb {
+                Some(deb) => package.with_control(&deb.control),
+                None => package,
+            })
+        })
+        .collect()
+}
+
+/// Fills in which version of each package is installed.
+async fn resolve_installed(
+    backend: &dyn PackageBackend,
+    packages: &mut [Package],
+) -> anyhow::Result<()> {
+    let names = packages
+        .iter()
+        .map(|package| package.id.name.clone())
+        .collect();
+    let installed = backend.resolve(vec![Filter::Installed], names).await?;
+
+    for package in packages {
+        package.installed = installed
+            .iter()
+            .filter_map(|installed| installed.package_id.parse::<PackageId>().ok())
+            .find(|installed| package.is_installed_as(installed));
+    }
+    Ok(())
+}
+
+/// Fills in which version of each package is installed from dpkg's database, reading its
+/// status file only if it was not read yet.
+async fn installed_from_status(
+    status: Option<Arc<Database>>,
+    packages: &mut [Package],
+) -> anyhow::Result<()> {
+    let database = match status {
+        Some(database) => database,
+        None => Arc::new(tokio::task::spawn_blocking(|| Database::read(dpkg::STATUS)).await??),
+    };
+    for package in packages {
+        package.installed = database
+            .find(&package.id.name, &package.id.arch)
+            .map(InstalledPackage::id);
+    }
+    Ok(())
+}
+
+/// Finds the newest version of each package in the repositories, if it is not older than
+/// the file's.
+async fn resolve_available(
+    backend: &dyn PackageBackend,
+    packages: &mut [Package],
+) -> anyhow::Result<()> {
+    let names = packages
+        .iter()
+        .map(|package| package.id.name.clone())
+        .collect();
+    let available = backend.resolve(vec![Filter::NotInstalled], names).await?;
+    let available: Vec<PackageId> = available
+        .iter()
+        .filter_map(|available| available.package_id.parse::<PackageId>().ok())
+        .filter(|available| available.data != "local")
+        .collect();
+
+    for package in packages {
+        package.available = available
+            .iter()
+            .filter(|available| package.is_installed_as(available))
+            .filter(|available| {
+                version::compare(&available.version, &package.id.version)
+                    .is_ok_and(|ordering| ordering != Ordering::Less)
+            })
+            .max_by(|a, b| version::compare(&a.version, &b.version).unwrap_or(Ordering::Equal))
+            .cloned();
+    }
+    Ok(())
+}
+
+/// Opens a `.deb` to read its control file, which is optional while PackageKit can read it.
+async fn read_deb(path: String) -> Option<Deb> {
+    let result = tokio::task::spawn_blocking(move || Deb::open(path)).await;
+    match result.map_err(anyhow::Error::from).and_then(|deb| deb) {
+        Ok(deb) => Some(deb),
+        Err(why) => {
+            eprintln!("failed to read control file: {why}");
+            None
         }
     }
 }
 
-pub fn install_packages_local(
+/// Hashes the contents of a file on a blocking thread.
+async fn hash_file(path: String) -> anyhow::Result<u64> {
+    tokio::task::spawn_blocking(move || {
+        let mut file = std::fs::File::open(&path)?;
+        let mut hasher = DefaultHasher::new();
+        let mut buffer = vec![0; 64 * 1024];
+        loop {
+            match file.read(&mut buffer)? {
+                0 => return Ok(hasher.finish()),
+                read => hasher.write(&buffer[..read]),
+            }
+        }
+    })
+    .await?
+}
+
+/// One transaction of [`install_packages_local`].
+enum InstallGroup {
+    /// Repository packages, by their package IDs.
+    Repository(Vec<String>),
+    /// Files, with the flags their actions need.
+    Files(Vec<String>, Vec<TransactionFlag>),
+}
+
+impl InstallGroup {
+    fn start(
+        self,
+        backend: &dyn PackageBackend,
+    ) -> BoxFuture<'static, anyhow::Result<EventStream>> {
+        match self {
+            InstallGroup::Repository(package_ids) => backend.install_packages(package_ids),
+            InstallGroup::Files(paths, flags) => backend.install(paths, flags),
+        }
+    }
+}
+
+/// Installs the files of these packages, allowing reinstalls and downgrades where needed.
+///
+/// Packages the user chose to take from the repositories are installed first, by their
+/// package IDs, as the files may depend on them. All files are then installed in one
+/// transaction, as the preview simulated them, which orders them by their dependencies
+/// and fails or succeeds as a whole. The events of both transactions form one stream; it
+/// stops if the first does not succeed, and only the last `Finished` event is passed on.
+///
+/// Callers must have had the user confirm the install, as reinstalls and downgrades replace
+/// what is on the system.
+pub async fn install_packages_local(
+    backend: Arc<dyn PackageBackend>,
     packages: Vec<Package>,
-    f: Box<dyn FnMut(u32) + 'static>,
-) -> anyhow::Result<bool> {
-    let mut paths = Vec::with_capacity(packages.len());
+) -> anyhow::Result<EventStream> {
+    let (repository, files): (Vec<Package>, Vec<Package>) = packages
+        .into_iter()
+        .partition(|package| package.repository_choice().is_some());
+
+    let mut groups: VecDeque<InstallGroup> = VecDeque::new();
+    if !repository.is_empty() {
+        groups.push_back(InstallGroup::Repository(repository_ids(&repository)));
+    }
+    if !files.is_empty() {
+        let paths = files.iter().map(|package| package.path.clone()).collect();
+        groups.push_back(InstallGroup::Files(paths, install_flags(&files)));
+    }
+
+    // Start the first transaction right away so that failing to start it is an error.
+    let Some(group) = groups.pop_front() else {
+        return Ok(stream::empty().boxed());
+    };
+    let events = group.start(&*backend).await?;
 
+    let events = stream::unfold(
+        (backend, groups, Some(events)),
+        |(backend, mut groups, mut events)| async move {
+            loop {
+                let current = match &mut events {
+                    Some(current) => current,
+                    None => {
+                        let group = groups.pop_front()?;
+                        match group.start(&*backend).await {
+                            Ok(next) => events.insert(next),
+                            Err(why) => return Some((Err(why), (backend, VecDeque::new(), None))),
+                        }
+                    }
+                };
+                match current.next().await {
+                    Some(Ok(TransactionEvent::Finished {
+                        exit: Exit::Success,
+                        ..
+                    })) if !groups.is_empty() => events = None,
+                    Some(event) => {
+                        if matches!(
+                            event,
+                            Err(_)
+                                | Ok(TransactionEvent::ErrorCode { .. })
+                                | Ok(TransactionEvent::Finished { .. })
+                        ) {
+                            groups.clear();
+                        }
+                        return Some((event, (backend, groups, events)));
+                    }
+                    // A transaction that ends without finishing stops the install too.
+                    None => return None,
+                }
+            }
+        },
+    );
+
+    Ok(events.boxed())
+}
+
+/// The package IDs of the repository versions chosen instead of these packages' files.
+pub fn repository_ids(packages: &[Package]) -> Vec<String> {
     packages
         .iter()
-        .for_each(|package| paths.push(package.path.as_str()));
+        .filter_map(|package| package.repository_choice())
+        .map(PackageId::to_string)
+        .collect()
+}
+
+/// The flags needed to carry out the actions of all packages, without duplicates.
+pub fn install_flags(packages: &[Package]) -> Vec<TransactionFlag> {
+    let mut flags = Vec::new();
+    for flag in packages
+        .iter()
+        .filter_map(|package| package.action().flag())
+    {
+        if !flags.contains(&flag) {
+            flags.push(flag);
+        }
+    }
+    flags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mock::MockBackend;
+    use crate::deb::tests::{build_deb, HELLO_CONTROL};
+
+    fn hello() -> TransactionDetails {
+        TransactionDetails {
+            package_id: String::from("hello;2.10-3;amd64;local"),
+            summary: String::from("example package based on GNU hello"),
+            description: String::new(),
+            url: String::new(),
+            license: String::new(),
+            size: 57_348,
+            download_size: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn same_contents_are_loaded_once() {
+        let dir = std::env::temp_dir().join(format!("wizard-loader-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let first = dir.join("hello_2.10-3_amd64.deb");
+        let copy = dir.join("copy.deb");
+        build_deb(&first, HELLO_CONTROL, ".gz");
+        std::fs::copy(&first, &copy).unwrap();
+        let (first, copy) = (
+            first.to_string_lossy().into_owned(),
+            copy.to_string_lossy().into_owned(),
+        );
+
+        // Only the first path is known to the backend, so the copy must come from the cache.
+        let backend: Arc<dyn PackageBackend> =
+            Arc::new(MockBackend::new().with_details(&first, vec![hello()]));
+        let loader = DetailsLoader::default();
+
+        let packages = loader
+            .load(backend.clone(), first.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(packages[0].path, first);
+
+        let packages = loader
+            .load(backend.clone(), copy.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(packages[0].path, copy);
+        assert_eq!(packages[0].id.name, "hello");
+        assert_eq!(packages[0].installed_size, Some(280 * 1024));
+        assert_eq!(packages[0].depends, "libc6 (>= 2.34)");
+        assert_eq!(packages[0].url, "https://www.gnu.org/software/hello/");
+
+        assert!(loader
+            .load(
+                backend,
+                dir.join("missing.deb").to_string_lossy().into_owned(),
+                None,
+            )
+            .await
+            .is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn control_file_is_read_without_packagekit() {
+        let dir = std::env::temp_dir().join(format!("wizard-control-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("hello_2.10-3_amd64.deb");
+        build_deb(&path, HELLO_CONTROL, ".xz");
+        let path = path.to_string_lossy().into_owned();
+
+        // The backend has no details for the file, as when PackageKit is not running.
+        let backend: Arc<dyn PackageBackend> = Arc::new(MockBackend::new());
+        let packages = DetailsLoader::default()
+            .load(backend, path.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(packages.len(), 1);
+        let package = &packages[0];
+        assert_eq!(package.path, path);
+        assert_eq!(package.id.to_string(), "hello;2.10-3;amd64;local");
+        assert_eq!(package.summary, "example package based on GNU hello");
+        assert!(package.description.starts_with("The GNU hello program"));
+        assert_eq!(package.url, "https://www.gnu.org/software/hello/");
+        assert_eq!(
+            (package.size, package.installed_size),
+            (280 * 1024, Some(280 * 1024))
+        );
+        assert_eq!(package.conflicts, "hello-traditional");
+
+        let broken = dir.join("broken.deb");
+        build_deb(&broken, "Package: hello\nVersion: 1.0\n", ".gz");
+        let backend: Arc<dyn PackageBackend> = Arc::new(MockBackend::new());
+        assert!(DetailsLoader::default()
+            .load(backend, broken.to_string_lossy().into_owned(), None)
+            .await
+            .is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    fn installed(version: &str) -> Package {
+        let mut package = Package::new(String::from("/tmp/hello.deb"), hello()).unwrap();
+        package.installed = Some(format!("hello;{version};amd64;installed").parse().unwrap());
+        package
+    }
+
+    #[tokio::test]
+    async fn files_are_installed_together() {
+        let mut app = Package::new(String::from("/tmp/app.deb"), hello()).unwrap();
+        app.id = "app;1.0;amd64;local".parse().unwrap();
+        app.depends = String::from("libapp1");
+        let mut lib = app.clone();
+        lib.path = String::from("/tmp/libapp1.deb");
+        lib.id = "libapp1;1.0;amd64;local".parse().unwrap();
+        lib.depends = String::new();
+        let tool = installed("2.10-3");
+        let mut data = app.clone();
+        data.path = String::from("/tmp/app-data.deb");
+        data.id = "app-data;1.0;all;local".parse().unwrap();
+        data.depends = String::new();
+        data.available = Some("app-data;1.1;all;debian-stable-main".parse().unwrap());
+        data.use_repository = true;
+
+        let backend = MockBackend::new().with_events(vec![
+            TransactionEvent::Percentage(50),
+            TransactionEvent::Finished {
+                exit: Exit::Success,
+                runtime: 100,
+            },
+        ]);
+        let events = install_packages_local(Arc::new(backend.clone()), vec![app, tool, data, lib])
+            .await
+            .unwrap();
+        // The files are only installed once the repository install has finished.
+        assert_eq!(backend.transactions(), ["install_packages"]);
+        let events: Vec<TransactionEvent> = events.map(|event| event.unwrap()).collect().await;
+        assert_eq!(backend.transactions(), ["install_packages", "install"]);
+
+        assert_eq!(
+            backend.repository_installs(),
+            [vec![String::from("app-data;1.1;all;debian-stable-main")]]
+        );
+        // One transaction in the order the files were added, which PackageKit reorders.
+        assert_eq!(
+            backend.installs(),
+            [(
+                vec![
+                    String::from("/tmp/app.deb"),
+                    String::from("/tmp/hello.deb"),
+                    String::from("/tmp/libapp1.deb"),
+                ],
+                vec![TransactionFlag::AllowReinstall]
+            )]
+        );
+        assert_eq!(events.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn failed_repository_install_stops_the_install() {
+        let mut lib = installed("2.10-3");
+        lib.available = Some("hello;2.10-4;amd64;debian-stable-main".parse().unwrap());
+        lib.use_repository = true;
+        let mut other = lib.clone();
+        other.path = String::from("/tmp/other.deb");
+        other.id = "other;1.0;amd64;local".parse().unwrap();
+        other.installed = None;
+        other.available = None;
+
+        let backend = MockBackend::new().with_events(vec![
+            TransactionEvent::ErrorCode {
+                code: crate::packagekit::Error::PackageCorrupt,
+                details: String::from("bad file"),
+            },
+            TransactionEvent::Finished {
+                exit: Exit::Failed,
+                runtime: 100,
+            },
+        ]);
+        let events = install_packages_local(Arc::new(backend.clone()), vec![lib, other])
+            .await
+            .unwrap();
+        let events: Vec<_> = events.collect().await;
+
+        assert_eq!(backend.repository_installs().len(), 1);
+        assert!(backend.installs().is_empty());
+        assert!(matches!(
+            events.last(),
+            Some(Ok(TransactionEvent::Finished {
+                exit: Exit::Failed,
+                ..
+            }))
+        ));
+    }
+
+    #[test]
+    fn actions_compare_versions() {
+        let package = Package::new(String::from("/tmp/hello.deb"), hello()).unwrap();
+        assert_eq!(package.action(), Action::Install);
+        assert_eq!(installed("2.9-1").action(), Action::Upgrade);
+        assert_eq!(installed("2.10-3").action(), Action::Reinstall);
+        assert_eq!(installed("1:2.9-1").action(), Action::Downgrade);
+        assert_eq!(installed("2.10~rc1-1").action(), Action::Upgrade);
+
+        let flags = install_flags(&[package, installed("2.10-3"), installed("2.10-3")]);
+        assert_eq!(flags, [TransactionFlag::AllowReinstall]);
+    }
+
+    #[tokio::test]
+    async fn repository_version_is_offered() {
+        let dir = std::env::temp_dir().join(format!("wizard-available-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("hello_2.10-3_amd64.deb");
+        build_deb(&path, HELLO_CONTROL, ".gz");
+        let path = path.to_string_lossy().into_owned();
+
+        let backend = MockBackend::new()
+            .with_details(&path, vec![hello()])
+            .with_available("hello;2.9-1;amd64;debian-oldstable-main")
+            .with_available("hello;2.10-4;amd64;debian-stable-main")
+            .with_available("hello;2.10-5;i386;debian-stable-main")
+            .with_events(vec![TransactionEvent::Finished {
+                exit: Exit::Success,
+                runtime: 100,
+            }]);
+        let mut packages = DetailsLoader::default()
+            .load(Arc::new(backend.clone()), path, None)
+            .await
+            .unwrap();
+
+        let available = packages[0].available.clone().unwrap();
+        assert_eq!(
+            available.to_string(),
+            "hello;2.10-4;amd64;debian-stable-main"
+        );
+        assert!(packages[0].repository_choice().is_none());
+
+        packages[0].use_repository = true;
+        let events = install_packages_local(Arc::new(backend.clone()), packages)
+            .await
+            .unwrap();
+        assert_eq!(events.count().await, 1);
+        assert_eq!(
+            backend.repository_installs(),
+            [vec![String::from("hello;2.10-4;amd64;debian-stable-main")]]
+        );
+        assert!(backend.installs().is_empty());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn installed_version_is_resolved() {
+        let dir = std::env::temp_dir().join(format!("wizard-resolve-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("hello_2.10-3_amd64.deb");
+        build_deb(&path, HELLO_CONTROL, ".gz");
+        let path = path.to_string_lossy().into_owned();
+
+        let backend: Arc<dyn PackageBackend> = Arc::new(
+            MockBackend::new()
+                .with_details(&path, vec![hello()])
+                .with_installed("hello;2.10-3;i386;installed:debian-stable-main")
+                .with_installed("hello;2.9-1;amd64;installed:debian-stable-main"),
+        );
+        let packages = DetailsLoader::default()
+            .load(backend, path, None)
+            .await
+            .unwrap();
+
+        let installed = packages[0].installed.as_ref().unwrap();
+        assert_eq!(installed.version, "2.9-1");
+        assert_eq!(packages[0].action(), Action::Upgrade);
 
-    let proxy = PackageKit::new()?;
-    if proxy.install_packages_files(&paths, f).is_ok() {
-        Ok(true)
-    } else {
-        Ok(false)
+        std::fs::remove_dir_all(dir).unwrap();
     }
 }
@@ -1,26 +1,29 @@
-use std::collections::HashMap;
+mod enums;
+mod event;
+#[cfg(test)]
+mod fake;
+mod package_id;
 
-use anyhow::anyhow;
+pub use enums::*;
+pub use event::*;
+pub use package_id::*;
+
+use std::time::Duration;
+
+use futures_util::{stream, stream::BoxStream, StreamExt};
 use packagekit_zbus::{
-    zbus::{blocking::Connection, zvariant},
-    PackageKit::PackageKitProxyBlocking,
-    Transaction::TransactionProxyBlocking,
+    zbus::{self, zvariant, Connection, PropertyStream},
+    PackageKit::PackageKitProxy,
+    Transaction::TransactionProxy,
 };
 
-#[derive(Debug)]
-pub struct TransactionDetails {
-    pub package_id: String,
-    pub summary: String,
-    pub description: String,
-    pub url: String,
-    pub license: String,
-    pub size: String,
-}
+pub type EventStream = BoxStream<'static, anyhow::Result<TransactionEvent>>;
 
 // https://github.com/PackageKit/PackageKit/blob/209aa62950e503494716fd046f8f5cb546bf57d4/lib/packagekit-glib2/pk-enum.h#L776-L798
 #[allow(dead_code)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[repr(u64)]
-enum TransactionFlag {
+pub enum TransactionFlag {
     None = 1 << 0,
     OnlyTrusted = 1 << 1,
     Simulate = 1 << 2,
@@ -31,125 +34,876 @@ enum TransactionFlag {
     Last = 1 << 7,
 }
 
-#[derive(Debug)]
+impl From<TransactionFlag> for u64 {
+    fn from(flag: TransactionFlag) -> Self {
+        flag as u64
+    }
+}
+
+/// Combines flags or filters into the bitfield PackageKit expects.
+pub fn bitfield<F: Copy + Into<u64>>(flags: &[F]) -> u64 {
+    flags.iter().fold(0, |bits, flag| bits | (*flag).into())
+}
+
+// https://github.com/PackageKit/PackageKit/blob/209aa62950e503494716fd046f8f5cb546bf57d4/lib/packagekit-glib2/pk-enum.h#L140-L171
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u64)]
+pub enum Filter {
+    None = 1 << 1,
+    Installed = 1 << 2,
+    NotInstalled = 1 << 3,
+    Development = 1 << 4,
+    NotDevelopment = 1 << 5,
+    Gui = 1 << 6,
+    NotGui = 1 << 7,
+    Free = 1 << 8,
+    NotFree = 1 << 9,
+    Visible = 1 << 10,
+    NotVisible = 1 << 11,
+    Supported = 1 << 12,
+    NotSupported = 1 << 13,
+    Basename = 1 << 14,
+    NotBasename = 1 << 15,
+    Newest = 1 << 16,
+    NotNewest = 1 << 17,
+    Arch = 1 << 18,
+    NotArch = 1 << 19,
+    Source = 1 << 20,
+    NotSource = 1 << 21,
+    Collections = 1 << 22,
+    NotCollections = 1 << 23,
+    Application = 1 << 24,
+    NotApplication = 1 << 25,
+    Downloaded = 1 << 26,
+    NotDownloaded = 1 << 27,
+}
+
+impl From<Filter> for u64 {
+    fn from(filter: Filter) -> Self {
+        filter as u64
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct PackageKit {
     connection: Connection,
 }
 
 impl PackageKit {
-    pub fn new() -> anyhow::Result<Self> {
-        let conn = Connection::system()?;
+    pub async fn new() -> anyhow::Result<Self> {
+        let conn = Connection::system().await?;
 
         Ok(Self { connection: conn })
     }
 
-    pub fn _proxy(&self) -> anyhow::Result<PackageKitProxyBlocking> {
-        Ok(PackageKitProxyBlocking::new(&self.connection)?)
+    /// Connects to a PackageKit service on the bus at `address` instead of the system bus.
+    #[cfg(test)]
+    pub async fn with_address(address: &str) -> anyhow::Result<Self> {
+        let conn = packagekit_zbus::zbus::ConnectionBuilder::address(address)?
+            .build()
+            .await?;
+
+        Ok(Self { connection: conn })
     }
 
-    pub fn transaction(&self) -> anyhow::Result<TransactionProxyBlocking> {
-        let pk = PackageKitProxyBlocking::new(&self.connection)?;
-        let tx_path = pk.create_transaction()?;
-        let tx = TransactionProxyBlocking::builder(&self.connection)
+    pub async fn proxy(&self) -> anyhow::Result<PackageKitProxy<'static>> {
+        Ok(PackageKitProxy::new(&self.connection).await?)
+    }
+
+    pub async fn transaction(&self) -> anyhow::Result<Transaction> {
+        let pk = self.proxy().await?;
+        let tx_path = pk.create_transaction().await?;
+        let proxy = TransactionProxy::builder(&self.connection)
             .destination("org.freedesktop.PackageKit")?
             .path(tx_path)?
-            .build()?;
+            .build()
+            .await?;
+
+        Ok(Transaction { proxy })
+    }
+
+    /// How long ago a transaction with this role last finished, if it ever did.
+    pub async fn time_since_action(&self, role: Role) -> anyhow::Result<Option<Duration>> {
+        let seconds = self
+            .proxy()
+            .await?
+            .get_time_since_action(role as u32)
+            .await?;
+        // PackageKit answers `G_MAXUINT` for roles it has no record of.
+        Ok((seconds != u32::MAX).then(|| Duration::from_secs(seconds.into())))
+    }
+}
+
+/// A PackageKit transaction object created by [`PackageKit::transaction`].
+#[derive(Debug, Clone)]
+pub struct Transaction {
+    proxy: TransactionProxy<'static>,
+}
+
+impl Transaction {
+    /// The object path of the transaction, which tells transactions apart.
+    pub fn path(&self) -> String {
+        self.proxy.inner().path().to_string()
+    }
+
+    /// Subscribes to the transaction's signals and to changes of its progress properties.
+    ///
+    /// This has to be called before the transaction is started, otherwise early signals
+    /// may be missed. The stream ends after the `Finished` signal or the first error.
+    pub async fn events(&self) -> anyhow::Result<EventStream> {
+        let signals = self.proxy.receive_all_signals().await?.flat_map(|signal| {
+            stream::iter(match TransactionEvent::from_signal(&signal) {
+                Ok(events) => events.into_iter().map(Ok).collect(),
+                Err(why) => vec![Err(why)],
+            })
+        });
+
+        let properties = stream::select_all([
+            property_events(
+                self.proxy.receive_allow_cancel_changed().await,
+                TransactionEvent::AllowCancel,
+            ),
+            property_events(
+                self.proxy.receive_percentage_changed().await,
+                TransactionEvent::Percentage,
+            ),
+            property_events(self.proxy.receive_status_changed().await, |status| {
+                TransactionEvent::Status(status.into())
+            }),
+            property_events(
+                self.proxy.receive_speed_changed().await,
+                TransactionEvent::Speed,
+            ),
+            property_events(
+                self.proxy.receive_elapsed_time_changed().await,
+                TransactionEvent::ElapsedTime,
+            ),
+            property_events(
+                self.proxy.receive_remaining_time_changed().await,
+                TransactionEvent::RemainingTime,
+            ),
+        ]);
+
+        let events = stream::select(signals, properties).boxed();
+        let events = stream::unfold(Some(events), |events| async move {
+            let mut events = events?;
+            let event = events.next().await?;
+            let finished = matches!(event, Ok(TransactionEvent::Finished { .. }) | Err(_));
+            Some((event, (!finished).then_some(events)))
+        });
+
+        Ok(events.boxed())
+    }
+
+    /// Reads the details of packages from the repositories, including their download size.
+    pub async fn get_details(&self, package_ids: &[&str]) -> anyhow::Result<EventStream> {
+        let events = self.events().await?;
+        self.proxy.get_details(package_ids).await?;
+        Ok(events)
+    }
+
+    pub async fn get_details_local(&self, files: &[&str]) -> anyhow::Result<EventStream> {
+        let events = self.events().await?;
+        self.proxy.get_details_local(files).await?;
+        Ok(events)
+    }
+
+    /// Looks up packages by name, keeping those matching every filter.
+    pub async fn resolve(&self, filters: &[Filter], names: &[&str]) -> anyhow::Result<EventStream> {
+        self.proxy
+            .set_hints(&["supports-plural-signals=true"])
+            .await?;
+        let events = self.events().await?;
+        self.proxy.resolve(bitfield(filters), names).await?;
+        Ok(events)
+    }
 
-        Ok(tx)
+    /// Lists the configured software sources, enabled or not.
+    pub async fn get_repo_list(&self) -> anyhow::Result<EventStream> {
+        let events = self.events().await?;
+        self.proxy.get_repo_list(Filter::None as u64).await?;
+        Ok(events)
     }
 
-    pub fn install_packages_files(
+    /// Enables or disables a software source.
+    pub async fn repo_enable(&self, repo_id: &str, enabled: bool) -> anyhow::Result<EventStream> {
+        self.proxy.set_hints(&["interactive=true"]).await?;
+        let events = self.events().await?;
+        self.proxy.repo_enable(repo_id, enabled).await?;
+        Ok(events)
+    }
+
+    /// Downloads the package lists of the enabled software sources.
+    pub async fn refresh_cache(&self, force: bool) -> anyhow::Result<EventStream> {
+        self.proxy.set_hints(&["interactive=true"]).await?;
+        let events = self.events().await?;
+        self.proxy.refresh_cache(force).await?;
+        Ok(events)
+    }
+
+    pub async fn install_files(
         &self,
+        flags: &[TransactionFlag],
         files: &[&str],
-        mut f: Box<dyn FnMut(u32) + 'static>,
-    ) -> anyhow::Result<()> {
-        let tx = self.transaction()?;
-        tx.set_hints(&["interactive=true"])?;
-        tx.set_hints(&["supports-plural-signals=true"])?;
-        println!("installing packages {:?}", files);
-        tx.install_files(TransactionFlag::None as u64, &files)?;
-        let _tx_packages = transaction_handle(tx, |total_percentage| {
-            f(total_percentage);
-        })?;
-        Ok(())
-    }
-}
-
-pub fn transaction_handle(
-    tx: TransactionProxyBlocking,
-    mut on_progress: impl FnMut(u32),
-) -> anyhow::Result<Vec<TransactionDetails>> {
+    ) -> anyhow::Result<EventStream> {
+        self.proxy
+            .set_hints(&["interactive=true", "supports-plural-signals=true"])
+            .await?;
+        let events = self.events().await?;
+        self.proxy.install_files(bitfield(flags), files).await?;
+        Ok(events)
+    }
+
+    pub async fn install_packages(
+        &self,
+        flags: &[TransactionFlag],
+        package_ids: &[&str],
+    ) -> anyhow::Result<EventStream> {
+        self.proxy
+            .set_hints(&["interactive=true", "supports-plural-signals=true"])
+            .await?;
+        let events = self.events().await?;
+        self.proxy
+            .install_packages(bitfield(flags), package_ids)
+            .await?;
+        Ok(events)
+    }
+
+    /// Removes installed packages, along with the packages that depend on them.
+    pub async fn remove_packages(
+        &self,
+        flags: &[TransactionFlag],
+        package_ids: &[&str],
+    ) -> anyhow::Result<EventStream> {
+        self.proxy
+            .set_hints(&["interactive=true", "supports-plural-signals=true"])
+            .await?;
+        let events = self.events().await?;
+        self.proxy
+            .remove_packages(bitfield(flags), package_ids, true, false)
+            .await?;
+        Ok(events)
+    }
+
+    pub async fn cancel(&self) -> anyhow::Result<()> {
+        Ok(self.proxy.cancel().await?)
+    }
+}
+
+/// Turns changes of a transaction property into events.
+fn property_events<T>(
+    changes: PropertyStream<'static, T>,
+    event: fn(T) -> TransactionEvent,
+) -> EventStream
+where
+    T: TryFrom<zvariant::OwnedValue> + Send + Sync + Unpin + 'static,
+    T::Error: Into<zbus::Error>,
+{
+    changes
+        .then(move |changed| async move {
+            changed
+                .get()
+                .await
+                .map(event)
+                .map_err(anyhow::Error::from)
+        })
+        .boxed()
+}
+
+/// The error a transaction reported through its `ErrorCode` signal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionError {
+    pub code: Error,
+    pub details: String,
+}
+
+impl std::fmt::Display for TransactionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (error code {})", self.details, self.code)
+    }
+}
+
+impl std::error::Error for TransactionError {}
+
+impl From<anyhow::Error> for TransactionError {
+    /// Recovers the PackageKit error, or wraps a failure that happened outside of PackageKit.
+    fn from(why: anyhow::Error) -> Self {
+        why.downcast().unwrap_or_else(|why| Self {
+            code: Error::Unknown,
+            details: format!("{why:#}"),
+        })
+    }
+}
+
+/// Drains a transaction's events, returning the packages it reported.
+pub async fn collect_packages(mut events: EventStream) -> anyhow::Result<Vec<TransactionPackage>> {
+    let mut packages = Vec::new();
+
+    while let Some(event) = events.next().await {
+        match event? {
+            TransactionEvent::Package(package) => packages.push(package),
+            TransactionEvent::ErrorCode { code, details } => {
+                return Err(TransactionError { code, details }.into());
+            }
+            _ => (),
+        }
+    }
+
+    Ok(packages)
+}
+
+/// Drains a transaction's events, failing if it reported an error.
+pub async fn finish(mut events: EventStream) -> anyhow::Result<()> {
+    while let Some(event) = events.next().await {
+        if let TransactionEvent::ErrorCode { code, details } = event? {
+            return Err(TransactionError { code, details }.into());
+        }
+    }
+    Ok(())
+}
+
+/// Drains a transaction's events, returning the software sources it reported.
+pub async fn collect_repositories(mut events: EventStream) -> anyhow::Result<Vec<Repository>> {
+    let mut repositories = Vec::new();
+
+    while let Some(event) = events.next().await {
+        match event? {
+            TransactionEvent::RepoDetail {
+                repo_id,
+                description,
+                enabled,
+            } => repositories.push(Repository {
+                id: repo_id,
+                description,
+                enabled,
+            }),
+            TransactionEvent::ErrorCode { code, details } => {
+                return Err(TransactionError { code, details }.into());
+            }
+            _ => (),
+        }
+    }
+
+    Ok(repositories)
+}
+
+/// Drains a transaction's events, returning the package details it reported.
+pub async fn collect_details(mut events: EventStream) -> anyhow::Result<Vec<TransactionDetails>> {
     let mut details = Vec::new();
 
-    for signal in tx.receive_all_signals()? {
-        if let Some(member) = signal.member() {
-            match member.as_str() {
-                "Details" => {
-                    let map = signal.body::<HashMap<String, zvariant::Value>>()?;
-                    let get_string = |key: &str| -> Option<String> {
-                        match map.get(key) {
-                            Some(zvariant::Value::Str(str)) => Some(str.to_string()),
-                            unknown => {
-                                println!(
-                                        "failed to find string for key {:?} in packagekit Details: found {:?} instead",
-                                        key,
-                                        unknown
-                                    );
-                                None
-                            }
-                        }
-                    };
-                    let size = match map.get("size") {
-                        Some(zvariant::Value::U64(number)) => {
-                            let size_in_mb = number / 1_000_000;
-                            format!("{} MB", size_in_mb)
-                        }
-                        _ => String::from("0 MB"),
-                    };
-
-                    let Some(package_id) = get_string("package-id") else {
-                        continue;
-                    };
-                    let summary = get_string("summary").unwrap_or_default();
-                    let description = get_string("description").unwrap_or_default();
-                    let url = get_string("url").unwrap_or_default();
-                    let license = get_string("license").unwrap_or_default();
-
-                    details.push(TransactionDetails {
-                        package_id,
-                        summary,
-                        description,
-                        url,
-                        license,
-                        size,
-                    });
-                }
-                "ErrorCode" => {
-                    // https://www.freedesktop.org/software/PackageKit/gtk-doc/Transaction.html#Transaction::ErrorCode
-                    let (code, details) = signal.body::<(u32, String)>()?;
-                    return Err(anyhow!("{details} (error code {code})"));
-                }
-                "ItemProgress" => {
-                    // https://www.freedesktop.org/software/PackageKit/gtk-doc/Transaction.html#Transaction::ItemProgress
-                    let (package_id, status, percentage) = signal.body::<(String, u32, u32)>()?;
-                    println!("Status {status} {} {percentage}", package_id);
-                    let total_percentage = tx.percentage().unwrap_or(percentage);
-                    on_progress(total_percentage)
-                }
-                "Package" => {
-                    // https://www.freedesktop.org/software/PackageKit/gtk-doc/Transaction.html#Transaction::Package
-                    let (info, package_id, _summary) = signal.body::<(u32, String, String)>()?;
-
-                    println!("Info {info} {}", package_id);
-                }
-                "Finished" => {
-                    break;
-                }
-                _ => {
-                    println!("unknown signal {}", member);
-                }
+    while let Some(event) = events.next().await {
+        match event? {
+            TransactionEvent::Details(tx_details) => details.push(tx_details),
+            TransactionEvent::ErrorCode { code, details } => {
+                return Err(TransactionError { code, details }.into());
             }
+            _ => (),
         }
     }
+
     Ok(details)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::fake::{Bus, Script, Service, Step};
+    use super::*;
+
+    const PATH: &str = "/tmp/hello_2.10-3_amd64.deb";
+    const PACKAGE_ID: &str = "hello;2.10-3;amd64;local";
+
+    /// Serves `script` on a private bus, or returns `None` if no bus can be started.
+    async fn packagekit(script: Script) -> Option<(Bus, Service, PackageKit)> {
+        let Some(bus) = Bus::spawn() else {
+            eprintln!("dbus-daemon is not available, skipping");
+            return None;
+        };
+        let service = bus.serve(script).await.unwrap();
+        let pk = PackageKit::with_address(bus.address()).await.unwrap();
+        Some((bus, service, pk))
+    }
+
+    async fn install(pk: &PackageKit) -> Vec<TransactionEvent> {
+        let tx = pk.transaction().await.unwrap();
+        tx.install_files(&[TransactionFlag::None], &[PATH])
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await
+    }
+
+    fn item_progress(events: &[TransactionEvent]) -> Vec<(Status, u32)> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                TransactionEvent::ItemProgress {
+                    status, percentage, ..
+                } => Some((*status, *percentage)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn get_details_local() {
+        let script = Script {
+            get_details_local: vec![
+                Step::Details {
+                    package_id: PACKAGE_ID,
+                    summary: "example package based on GNU hello",
+                    size: 57_348,
+                    download_size: 0,
+                },
+                Step::Finished(Exit::Success),
+            ],
+            ..Script::default()
+        };
+        let Some((_bus, _service, pk)) = packagekit(script).await else {
+            return;
+        };
+
+        let tx = pk.transaction().await.unwrap();
+        let details = collect_details(tx.get_details_local(&[PATH]).await.unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(details.len(), 1);
+        assert_eq!(details[0].package_id, PACKAGE_ID);
+        assert_eq!(details[0].summary, "example package based on GNU hello");
+    }
+
+    #[tokio::test]
+    async fn get_details() {
+        let script = Script {
+            get_details: vec![
+                Step::Details {
+                    package_id: "libc6;2.36-9;amd64;debian-stable-main",
+                    summary: "GNU C Library: Shared libraries",
+                    size: 12_988_000,
+                    download_size: 2_757_000,
+                },
+                Step::Finished(Exit::Success),
+            ],
+            ..Script::default()
+        };
+        let Some((_bus, _service, pk)) = packagekit(script).await else {
+            return;
+        };
+
+        let tx = pk.transaction().await.unwrap();
+        let details = collect_details(
+            tx.get_details(&["libc6;2.36-9;amd64;debian-stable-main"])
+                .await
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(details.len(), 1);
+        assert_eq!(details[0].download_size, 2_757_000);
+    }
+
+    #[tokio::test]
+    async fn get_details_local_error() {
+        let script = Script {
+            get_details_local: vec![
+                Step::ErrorCode(Error::PackageCorrupt, "failed to read control file"),
+                Step::Finished(Exit::Failed),
+            ],
+            ..Script::default()
+        };
+        let Some((_bus, _service, pk)) = packagekit(script).await else {
+            return;
+        };
+
+        let tx = pk.transaction().await.unwrap();
+        let why = collect_details(tx.get_details_local(&[PATH]).await.unwrap())
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            why.to_string(),
+            "failed to read control file (error code package-corrupt)"
+        );
+        assert_eq!(TransactionError::from(why).code, Error::PackageCorrupt);
+    }
+
+    #[tokio::test]
+    async fn resolve() {
+        let script = Script {
+            resolve: vec![
+                Step::Package {
+                    info: Info::Installed,
+                    package_id: "hello;2.10-2;amd64;installed:debian-stable-main",
+                    summary: "example package based on GNU hello",
+                },
+                Step::Finished(Exit::Success),
+            ],
+            ..Script::default()
+        };
+        let Some((_bus, _service, pk)) = packagekit(script).await else {
+            return;
+        };
+
+        let tx = pk.transaction().await.unwrap();
+        let packages =
+            collect_packages(tx.resolve(&[Filter::Installed], &["hello"]).await.unwrap())
+                .await
+                .unwrap();
+
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].info, Info::Installed);
+        assert_eq!(
+            packages[0].package_id,
+            "hello;2.10-2;amd64;installed:debian-stable-main"
+        );
+    }
+
+    #[tokio::test]
+    async fn get_repo_list() {
+        let script = Script {
+            get_repo_list: vec![
+                Step::RepoDetail {
+                    repo_id: "debian-stable-main",
+                    description: "Debian stable main",
+                    enabled: true,
+                },
+                Step::RepoDetail {
+                    repo_id: "debian-stable-backports-main",
+                    description: "Debian stable backports main",
+                    enabled: false,
+                },
+                Step::Finished(Exit::Success),
+            ],
+            ..Script::default()
+        };
+        let Some((_bus, _service, pk)) = packagekit(script).await else {
+            return;
+        };
+
+        let tx = pk.transaction().await.unwrap();
+        let repositories = collect_repositories(tx.get_repo_list().await.unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(repositories.len(), 2);
+        assert_eq!(repositories[1].id, "debian-stable-backports-main");
+        assert!(!repositories[1].enabled);
+    }
+
+    #[tokio::test]
+    async fn repo_enable_error() {
+        let script = Script {
+            repo_enable: vec![
+                Step::ErrorCode(Error::RepoNotFound, "cannot find repo debian-nope"),
+                Step::Finished(Exit::Failed),
+            ],
+            refresh_cache: vec![Step::Percentage(50), Step::Finished(Exit::Success)],
+            ..Script::default()
+        };
+        let Some((_bus, _service, pk)) = packagekit(script).await else {
+            return;
+        };
+
+        let tx = pk.transaction().await.unwrap();
+        let why = finish(tx.repo_enable("debian-nope", true).await.unwrap())
+            .await
+            .unwrap_err();
+        assert_eq!(TransactionError::from(why).code, Error::RepoNotFound);
+
+        let tx = pk.transaction().await.unwrap();
+        finish(tx.refresh_cache(false).await.unwrap())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn install_packages() {
+        let script = Script {
+            install_packages: vec![
+                Step::Package {
+                    info: Info::Installing,
+                    package_id: "hello;2.10-4;amd64;debian-stable-main",
+                    summary: "",
+                },
+                Step::Finished(Exit::Success),
+            ],
+            ..Script::default()
+        };
+        let Some((_bus, _service, pk)) = packagekit(script).await else {
+            return;
+        };
+
+        let tx = pk.transaction().await.unwrap();
+        let events = tx
+            .install_packages(
+                &[TransactionFlag::OnlyTrusted, TransactionFlag::Simulate],
+                &["hello;2.10-4;amd64;debian-stable-main"],
+            )
+            .await
+            .unwrap();
+        let packages = collect_packages(events).await.unwrap();
+        assert_eq!(packages[0].info, Info::Installing);
+    }
+
+    #[tokio::test]
+    async fn remove_packages() {
+        let script = Script {
+            remove_packages: vec![
+                Step::Package {
+                    info: Info::Removing,
+                    package_id: "hello;2.10-3;amd64;installed:debian-stable-main",
+                    summary: "",
+                },
+                Step::Finished(Exit::Success),
+            ],
+            ..Script::default()
+        };
+        let Some((_bus, _service, pk)) = packagekit(script).await else {
+            return;
+        };
+
+        let tx = pk.transaction().await.unwrap();
+        let events = tx
+            .remove_packages(
+                &[TransactionFlag::None],
+                &["hello;2.10-3;amd64;installed:debian-stable-main"],
+            )
+            .await
+            .unwrap();
+        let packages = collect_packages(events).await.unwrap();
+        assert_eq!(packages[0].info, Info::Removing);
+    }
+
+    #[tokio::test]
+    async fn time_since_refresh() {
+        let script = Script {
+            time_since_refresh: Some(7200),
+            ..Script::default()
+        };
+        let Some((_bus, _service, pk)) = packagekit(script).await else {
+            return;
+        };
+
+        assert_eq!(
+            pk.time_since_action(Role::RefreshCache).await.unwrap(),
+            Some(Duration::from_secs(7200))
+        );
+        assert_eq!(
+            pk.time_since_action(Role::InstallFiles).await.unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn flags_are_combined() {
+        assert_eq!(bitfield::<TransactionFlag>(&[]), 0);
+        assert_eq!(
+            bitfield(&[
+                TransactionFlag::AllowReinstall,
+                TransactionFlag::AllowDowngrade
+            ]),
+            (1 << 4) | (1 << 6)
+        );
+        assert_eq!(
+            bitfield(&[Filter::Installed, Filter::Arch]),
+            (1 << 2) | (1 << 18)
+        );
+    }
+
+    #[tokio::test]
+    async fn install_files() {
+        let script = Script {
+            install_files: vec![
+                Step::Percentage(0),
+                Step::ItemProgress {
+                    package_id: PACKAGE_ID,
+                    status: Status::Download,
+                    percentage: 100,
+                },
+                Step::Percentage(50),
+                Step::ItemProgress {
+                    package_id: PACKAGE_ID,
+                    status: Status::Install,
+                    percentage: 100,
+                },
+                Step::Percentage(100),
+                Step::Finished(Exit::Success),
+            ],
+            ..Script::default()
+        };
+        let Some((_bus, service, pk)) = packagekit(script).await else {
+            return;
+        };
+
+        let events = install(&pk).await;
+
+        assert_eq!(service.installs(), [vec![PATH.to_string()]]);
+        assert_eq!(
+            item_progress(&events),
+            [(Status::Download, 100), (Status::Install, 100)]
+        );
+        assert!(matches!(
+            events.last(),
+            Some(TransactionEvent::Finished {
+                exit: Exit::Success,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn install_files_error() {
+        let script = Script {
+            install_files: vec![
+                Step::ErrorCode(
+                    Error::DepResolutionFailed,
+                    "hello: Depends: libc6 (>= 9.0) but it is not installable",
+                ),
+                Step::Finished(Exit::Failed),
+            ],
+            ..Script::default()
+        };
+        let Some((_bus, _service, pk)) = packagekit(script).await else {
+            return;
+        };
+
+        let events = install(&pk).await;
+
+        assert!(events.iter().any(|event| matches!(
+            event,
+            TransactionEvent::ErrorCode {
+                code: Error::DepResolutionFailed,
+                details,
+            } if details.starts_with("hello: Depends: libc6")
+        )));
+        assert!(matches!(
+            events.last(),
+            Some(TransactionEvent::Finished {
+                exit: Exit::Failed,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn install_files_partial_progress() {
+        let script = Script {
+            install_files: vec![
+                Step::ItemProgress {
+                    package_id: PACKAGE_ID,
+                    status: Status::Download,
+                    percentage: 30,
+                },
+                Step::ItemProgress {
+                    package_id: PACKAGE_ID,
+                    status: Status::Download,
+                    percentage: 60,
+                },
+                Step::ErrorCode(Error::PackageDownloadFailed, "connection reset by peer"),
+                Step::Finished(Exit::Failed),
+            ],
+            ..Script::default()
+        };
+        let Some((_bus, _service, pk)) = packagekit(script).await else {
+            return;
+        };
+
+        let events = install(&pk).await;
+
+        assert_eq!(
+            item_progress(&events),
+            [(Status::Download, 30), (Status::Download, 60)]
+        );
+        assert!(events.iter().any(|event| matches!(
+            event,
+            TransactionEvent::ErrorCode {
+                code: Error::PackageDownloadFailed,
+                ..
+            }
+        )));
+    }
+
+    #[tokio::test]
+    async fn progress_properties() {
+        let script = Script {
+            install_files: vec![
+                Step::Status(Status::Download),
+                Step::Speed(250_000),
+                Step::Status(Status::Install),
+            ],
+            ..Script::default()
+        };
+        let Some((_bus, _service, pk)) = packagekit(script).await else {
+            return;
+        };
+
+        let tx = pk.transaction().await.unwrap();
+        let mut events = tx
+            .install_files(&[TransactionFlag::None], &[PATH])
+            .await
+            .unwrap();
+
+        // Property streams yield the latest value, so intermediate changes may be skipped.
+        let mut status = None;
+        let mut speed = None;
+        while status != Some(Status::Install) || speed.is_none() {
+            match events.next().await.unwrap().unwrap() {
+                TransactionEvent::Status(changed) => status = Some(changed),
+                TransactionEvent::Speed(bytes) => speed = Some(bytes),
+                _ => (),
+            }
+        }
+
+        assert_eq!(speed, Some(250_000));
+    }
+
+    #[tokio::test]
+    async fn cancel() {
+        let script = Script {
+            install_files: vec![
+                Step::AllowCancel(true),
+                Step::ItemProgress {
+                    package_id: PACKAGE_ID,
+                    status: Status::Download,
+                    percentage: 10,
+                },
+            ],
+            ..Script::default()
+        };
+        let Some((_bus, _service, pk)) = packagekit(script).await else {
+            return;
+        };
+
+        let tx = pk.transaction().await.unwrap();
+        let mut events = tx
+            .install_files(&[TransactionFlag::None], &[PATH])
+            .await
+            .unwrap();
+
+        let mut cancellable = false;
+        while !cancellable {
+            let event = events.next().await.unwrap().unwrap();
+            cancellable = matches!(event, TransactionEvent::AllowCancel(true));
+        }
+        tx.cancel().await.unwrap();
+
+        let events: Vec<_> = events.map(Result::unwrap).collect().await;
+        assert!(events.iter().any(|event| matches!(
+            event,
+            TransactionEvent::ErrorCode {
+                code: Error::TransactionCancelled,
+                ..
+            }
+        )));
+        assert!(matches!(
+            events.last(),
+            Some(TransactionEvent::Finished {
+                exit: Exit::Cancelled,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,304 @@
+//! Typed versions of the enumerations PackageKit sends over D-Bus as `u32` codes.
+//!
+//! https://github.com/PackageKit/PackageKit/blob/main/lib/packagekit-glib2/pk-enum.h
+
+macro_rules! pk_enum {
+    (
+        $(#[$meta:meta])*
+        pub enum $name:ident {
+            $($variant:ident = $value:literal => $text:literal,)*
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+        #[repr(u32)]
+        pub enum $name {
+            #[default]
+            $($variant = $value,)*
+        }
+
+        impl $name {
+            /// The identifier PackageKit uses for this value, e.g. in `pkcon` output.
+            pub fn as_str(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $text,)*
+                }
+            }
+        }
+
+        impl From<u32> for $name {
+            fn from(value: u32) -> Self {
+                match value {
+                    $($value => Self::$variant,)*
+                    _ => Self::default(),
+                }
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+    };
+}
+
+pk_enum! {
+    /// The kind of action a `Package` signal refers to.
+    pub enum Info {
+        Unknown = 0 => "unknown",
+        Installed = 1 => "installed",
+        Available = 2 => "available",
+        Low = 3 => "low",
+        Enhancement = 4 => "enhancement",
+        Normal = 5 => "normal",
+        Bugfix = 6 => "bugfix",
+        Important = 7 => "important",
+        Security = 8 => "security",
+        Blocked = 9 => "blocked",
+        Downloading = 10 => "downloading",
+        Updating = 11 => "updating",
+        Installing = 12 => "installing",
+        Removing = 13 => "removing",
+        Cleanup = 14 => "cleanup",
+        Obsoleting = 15 => "obsoleting",
+        CollectionInstalled = 16 => "collection-installed",
+        CollectionAvailable = 17 => "collection-available",
+        Finished = 18 => "finished",
+        Reinstalling = 19 => "reinstalling",
+        Downgrading = 20 => "downgrading",
+        Preparing = 21 => "preparing",
+        Decompressing = 22 => "decompressing",
+        Untrusted = 23 => "untrusted",
+        Trusted = 24 => "trusted",
+        Unavailable = 25 => "unavailable",
+        Critical = 26 => "critical",
+    }
+}
+
+pk_enum! {
+    /// What a transaction, or a single package within it, is currently doing.
+    pub enum Status {
+        Unknown = 0 => "unknown",
+        Wait = 1 => "wait",
+        Setup = 2 => "setup",
+        Running = 3 => "running",
+        Query = 4 => "query",
+        Info = 5 => "info",
+        Remove = 6 => "remove",
+        RefreshCache = 7 => "refresh-cache",
+        Download = 8 => "download",
+        Install = 9 => "install",
+        Update = 10 => "update",
+        Cleanup = 11 => "cleanup",
+        Obsolete = 12 => "obsolete",
+        DepResolve = 13 => "dep-resolve",
+        SigCheck = 14 => "sig-check",
+        TestCommit = 15 => "test-commit",
+        Commit = 16 => "commit",
+        Request = 17 => "request",
+        Finished = 18 => "finished",
+        Cancel = 19 => "cancel",
+        DownloadRepository = 20 => "download-repository",
+        DownloadPackagelist = 21 => "download-packagelist",
+        DownloadFilelist = 22 => "download-filelist",
+        DownloadChangelog = 23 => "download-changelog",
+        DownloadGroup = 24 => "download-group",
+        DownloadUpdateinfo = 25 => "download-updateinfo",
+        Repackaging = 26 => "repackaging",
+        LoadingCache = 27 => "loading-cache",
+        ScanApplications = 28 => "scan-applications",
+        GeneratePackageList = 29 => "generate-package-list",
+        WaitingForLock = 30 => "waiting-for-lock",
+        WaitingForAuth = 31 => "waiting-for-auth",
+        ScanProcessList = 32 => "scan-process-list",
+        CheckExecutableFiles = 33 => "check-executable-files",
+        CheckLibraries = 34 => "check-libraries",
+        CopyFiles = 35 => "copy-files",
+        RunHook = 36 => "run-hook",
+    }
+}
+
+pk_enum! {
+    /// How a transaction ended.
+    pub enum Exit {
+        Unknown = 0 => "unknown",
+        Success = 1 => "success",
+        Failed = 2 => "failed",
+        Cancelled = 3 => "cancelled",
+        KeyRequired = 4 => "key-required",
+        EulaRequired = 5 => "eula-required",
+        Killed = 6 => "killed",
+        MediaChangeRequired = 7 => "media-change-required",
+        NeedUntrusted = 8 => "need-untrusted",
+        CancelledPriority = 9 => "cancelled-priority",
+        SkipTransaction = 10 => "skip-transaction",
+        RepairRequired = 11 => "repair-required",
+    }
+}
+
+pk_enum! {
+    /// The error reported by the `ErrorCode` signal.
+    #[allow(clippy::enum_variant_names)]
+    pub enum Error {
+        Unknown = 0 => "unknown",
+        Oom = 1 => "out-of-memory",
+        NoNetwork = 2 => "no-network",
+        NotSupported = 3 => "not-supported",
+        InternalError = 4 => "internal-error",
+        GpgFailure = 5 => "gpg-failure",
+        PackageIdInvalid = 6 => "package-id-invalid",
+        PackageNotInstalled = 7 => "package-not-installed",
+        PackageNotFound = 8 => "package-not-found",
+        PackageAlreadyInstalled = 9 => "package-already-installed",
+        PackageDownloadFailed = 10 => "package-download-failed",
+        GroupNotFound = 11 => "group-not-found",
+        GroupListInvalid = 12 => "group-list-invalid",
+        DepResolutionFailed = 13 => "dep-resolution-failed",
+        FilterInvalid = 14 => "filter-invalid",
+        CreateThreadFailed = 15 => "create-thread-failed",
+        TransactionError = 16 => "transaction-error",
+        TransactionCancelled = 17 => "transaction-cancelled",
+        NoCache = 18 => "no-cache",
+        RepoNotFound = 19 => "repo-not-found",
+        CannotRemoveSystemPackage = 20 => "cannot-remove-system-package",
+        ProcessKill = 21 => "process-kill",
+        FailedInitialization = 22 => "failed-initialization",
+        FailedFinalise = 23 => "failed-finalise",
+        FailedConfigParsing = 24 => "failed-config-parsing",
+        CannotCancel = 25 => "cannot-cancel",
+        CannotGetLock = 26 => "cannot-get-lock",
+        NoPackagesToUpdate = 27 => "no-packages-to-update",
+        CannotWriteRepoConfig = 28 => "cannot-write-repo-config",
+        LocalInstallFailed = 29 => "local-install-failed",
+        BadGpgSignature = 30 => "bad-gpg-signature",
+        MissingGpgSignature = 31 => "missing-gpg-signature",
+        CannotInstallSourcePackage = 32 => "cannot-install-source-package",
+        RepoConfigurationError = 33 => "repo-configuration-error",
+        NoLicenseAgreement = 34 => "no-license-agreement",
+        FileConflicts = 35 => "file-conflicts",
+        PackageConflicts = 36 => "package-conflicts",
+        RepoNotAvailable = 37 => "repo-not-available",
+        InvalidPackageFile = 38 => "invalid-package-file",
+        PackageInstallBlocked = 39 => "package-install-blocked",
+        PackageCorrupt = 40 => "package-corrupt",
+        AllPackagesAlreadyInstalled = 41 => "all-packages-already-installed",
+        FileNotFound = 42 => "file-not-found",
+        NoMoreMirrorsToTry = 43 => "no-more-mirrors-to-try",
+        NoDistroUpgradeData = 44 => "no-distro-upgrade-data",
+        IncompatibleArchitecture = 45 => "incompatible-architecture",
+        NoSpaceOnDevice = 46 => "no-space-on-device",
+        MediaChangeRequired = 47 => "media-change-required",
+        NotAuthorized = 48 => "not-authorized",
+        UpdateNotFound = 49 => "update-not-found",
+        CannotInstallRepoUnsigned = 50 => "cannot-install-repo-unsigned",
+        CannotUpdateRepoUnsigned = 51 => "cannot-update-repo-unsigned",
+        CannotGetFilelist = 52 => "cannot-get-filelist",
+        CannotGetRequires = 53 => "cannot-get-requires",
+        CannotDisableRepository = 54 => "cannot-disable-repository",
+        RestrictedDownload = 55 => "restricted-download",
+        PackageFailedToConfigure = 56 => "package-failed-to-configure",
+        PackageFailedToBuild = 57 => "package-failed-to-build",
+        PackageFailedToInstall = 58 => "package-failed-to-install",
+        PackageFailedToRemove = 59 => "package-failed-to-remove",
+        UpdateFailedDueToRunningProcess = 60 => "failed-due-to-running-process",
+        PackageDatabaseChanged = 61 => "package-database-changed",
+        ProvideTypeNotSupported = 62 => "provide-type-not-supported",
+        InstallRootInvalid = 63 => "install-root-invalid",
+        CannotFetchSources = 64 => "cannot-fetch-sources",
+        CancelledPriority = 65 => "cancelled-priority",
+        UnfinishedTransaction = 66 => "unfinished-transaction",
+        LockRequired = 67 => "lock-required",
+        RepoAlreadySet = 68 => "repo-already-set",
+    }
+}
+
+pk_enum! {
+    /// The kind of restart a package requires after it was installed or updated.
+    pub enum Restart {
+        Unknown = 0 => "unknown",
+        None = 1 => "none",
+        Application = 2 => "application",
+        Session = 3 => "session",
+        System = 4 => "system",
+        SecuritySession = 5 => "security-session",
+        SecuritySystem = 6 => "security-system",
+    }
+}
+
+pk_enum! {
+    /// The method a transaction was created for.
+    pub enum Role {
+        Unknown = 0 => "unknown",
+        Cancel = 1 => "cancel",
+        DependsOn = 2 => "depends-on",
+        GetDetails = 3 => "get-details",
+        GetFiles = 4 => "get-files",
+        GetPackages = 5 => "get-packages",
+        GetRepoList = 6 => "get-repo-list",
+        RequiredBy = 7 => "required-by",
+        GetUpdateDetail = 8 => "get-update-detail",
+        GetUpdates = 9 => "get-updates",
+        InstallFiles = 10 => "install-files",
+        InstallPackages = 11 => "install-packages",
+        InstallSignature = 12 => "install-signature",
+        RefreshCache = 13 => "refresh-cache",
+        RemovePackages = 14 => "remove-packages",
+        RepoEnable = 15 => "repo-enable",
+        RepoSetData = 16 => "repo-set-data",
+        Resolve = 17 => "resolve",
+        SearchDetails = 18 => "search-details",
+        SearchFile = 19 => "search-file",
+        SearchGroup = 20 => "search-group",
+        SearchName = 21 => "search-name",
+        UpdatePackages = 22 => "update-packages",
+        WhatProvides = 23 => "what-provides",
+        AcceptEula = 24 => "accept-eula",
+        DownloadPackages = 25 => "download-packages",
+        GetDistroUpgrades = 26 => "get-distro-upgrades",
+        GetCategories = 27 => "get-categories",
+        GetOldTransactions = 28 => "get-old-transactions",
+        UpgradeSystem = 29 => "upgrade-system",
+        RepairSystem = 30 => "repair-system",
+        GetDetailsLocal = 31 => "get-details-local",
+        GetFilesLocal = 32 => "get-files-local",
+        RepoRemove = 33 => "repo-remove",
+    }
+}
+
+pk_enum! {
+    /// The kind of media a `MediaChangeRequired` signal asks for.
+    pub enum MediaType {
+        Unknown = 0 => "unknown",
+        Cd = 1 => "cd",
+        Dvd = 2 => "dvd",
+        Disc = 3 => "disc",
+    }
+}
+
+pk_enum! {
+    /// The kind of key a `RepoSignatureRequired` signal asks to be trusted.
+    pub enum SigType {
+        Unknown = 0 => "unknown",
+        Gpg = 1 => "gpg",
+    }
+}
+
+pk_enum! {
+    /// The release channel of a `DistroUpgrade` signal.
+    pub enum DistroUpgrade {
+        Unknown = 0 => "unknown",
+        Stable = 1 => "stable",
+        Unstable = 2 => "unstable",
+    }
+}
+
+pk_enum! {
+    /// The maturity of an update reported by `UpdateDetail`.
+    pub enum UpdateState {
+        Unknown = 0 => "unknown",
+        Stable = 1 => "stable",
+        Unstable = 2 => "unstable",
+        Testing = 3 => "testing",
+    }
+}
@@ -0,0 +1,576 @@
+//! Decoding of the signals emitted on `org.freedesktop.PackageKit.Transaction`.
+//!
+//! https://www.freedesktop.org/software/PackageKit/gtk-doc/Transaction.html
+
+use std::collections::HashMap;
+
+use packagekit_zbus::zbus::{zvariant, Message};
+
+use super::enums::{
+    DistroUpgrade, Error, Exit, Info, MediaType, Restart, Role, SigType, Status, UpdateState,
+};
+
+#[derive(Debug, Clone)]
+pub struct TransactionDetails {
+    pub package_id: String,
+    pub summary: String,
+    pub description: String,
+    pub url: String,
+    pub license: String,
+    /// The size of the installed package in bytes.
+    pub size: u64,
+    /// The bytes still to be downloaded, 0 for local files and packages already cached.
+    pub download_size: u64,
+}
+
+/// A package reported by the `Package` or `Packages` signals.
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionPackage {
+    pub info: Info,
+    pub package_id: String,
+    pub summary: String,
+}
+
+/// A software source reported by the `RepoDetail` signal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repository {
+    pub id: String,
+    pub description: String,
+    pub enabled: bool,
+}
+
+/// An update reported by the `UpdateDetail` or `UpdateDetails` signals.
+#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpdateDetail {
+    pub package_id: String,
+    pub updates: Vec<String>,
+    pub obsoletes: Vec<String>,
+    pub vendor_urls: Vec<String>,
+    pub bugzilla_urls: Vec<String>,
+    pub cve_urls: Vec<String>,
+    pub restart: Restart,
+    pub update_text: String,
+    pub changelog: String,
+    pub state: UpdateState,
+    pub issued: String,
+    pub updated: String,
+}
+
+/// Events emitted by a PackageKit transaction until it finishes.
+///
+/// The plural `Packages` and `UpdateDetails` signals are flattened into one event per
+/// item, so consumers only have to handle the singular variants.
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub enum TransactionEvent {
+    /// Whether the transaction can currently be cancelled.
+    AllowCancel(bool),
+    Category {
+        parent_id: String,
+        cat_id: String,
+        name: String,
+        summary: String,
+        icon: String,
+    },
+    Destroy,
+    Details(TransactionDetails),
+    /// The time since the transaction started, in seconds.
+    ElapsedTime(u32),
+    DistroUpgrade {
+        kind: DistroUpgrade,
+        name: String,
+        summary: String,
+    },
+    ErrorCode {
+        code: Error,
+        details: String,
+    },
+    EulaRequired {
+        eula_id: String,
+        package_id: String,
+        vendor_name: String,
+        license_agreement: String,
+    },
+    Files {
+        package_id: String,
+        files: Vec<String>,
+    },
+    Finished {
+        exit: Exit,
+        runtime: u32,
+    },
+    ItemProgress {
+        package_id: String,
+        status: Status,
+        percentage: u32,
+    },
+    MediaChangeRequired {
+        media_type: MediaType,
+        media_id: String,
+        media_text: String,
+    },
+    Package(TransactionPackage),
+    /// The overall progress of the transaction, or 101 if unknown.
+    Percentage(u32),
+    RepoDetail {
+        repo_id: String,
+        description: String,
+        enabled: bool,
+    },
+    RepoSignatureRequired {
+        package_id: String,
+        repository_name: String,
+        key_url: String,
+        key_userid: String,
+        key_id: String,
+        key_fingerprint: String,
+        key_timestamp: String,
+        sig_type: SigType,
+    },
+    /// The estimated time until the transaction finishes, in seconds, or 0 if unknown.
+    RemainingTime(u32),
+    RequireRestart {
+        restart: Restart,
+        package_id: String,
+    },
+    /// The download speed in bytes per second, or 0 if nothing is being downloaded.
+    Speed(u32),
+    /// What the transaction as a whole is currently doing.
+    Status(Status),
+    Transaction {
+        object_path: String,
+        timespec: String,
+        succeeded: bool,
+        role: Role,
+        duration: u32,
+        data: String,
+        uid: u32,
+        cmdline: String,
+    },
+    UpdateDetail(UpdateDetail),
+}
+
+type UpdateDetailBody = (
+    String,
+    Vec<String>,
+    Vec<String>,
+    Vec<String>,
+    Vec<String>,
+    Vec<String>,
+    u32,
+    String,
+    String,
+    u32,
+    String,
+    String,
+);
+
+impl From<UpdateDetailBody> for UpdateDetail {
+    fn from(body: UpdateDetailBody) -> Self {
+        let (
+            package_id,
+            updates,
+            obsoletes,
+            vendor_urls,
+            bugzilla_urls,
+            cve_urls,
+            restart,
+            update_text,
+            changelog,
+            state,
+            issued,
+            updated,
+        ) = body;
+
+        Self {
+            package_id,
+            updates,
+            obsoletes,
+            vendor_urls,
+            bugzilla_urls,
+            cve_urls,
+            restart: restart.into(),
+            update_text,
+            changelog,
+            state: state.into(),
+            issued,
+            updated,
+        }
+    }
+}
+
+impl TransactionEvent {
+    /// Decodes a signal received from a transaction object.
+    ///
+    /// Returns an empty list for unknown signals and for those that carry nothing of use,
+    /// such as a `Details` signal without a package ID.
+    pub fn from_signal(signal: &Message) -> anyhow::Result<Vec<Self>> {
+        let Some(member) = signal.member() else {
+            return Ok(Vec::new());
+        };
+
+        let event = match member.as_str() {
+            "Category" => {
+                let (parent_id, cat_id, name, summary, icon) =
+                    signal.body::<(String, String, String, String, String)>()?;
+                Self::Category {
+                    parent_id,
+                    cat_id,
+                    name,
+                    summary,
+                    icon,
+                }
+            }
+            "Destroy" => Self::Destroy,
+            "Details" => {
+                let map = signal.body::<HashMap<String, zvariant::Value>>()?;
+                match details_from_map(&map) {
+                    Some(details) => Self::Details(details),
+                    None => {
+                        eprintln!("ignoring details without a package ID");
+                        return Ok(Vec::new());
+                    }
+                }
+            }
+            "DistroUpgrade" => {
+                let (kind, name, summary) = signal.body::<(u32, String, String)>()?;
+                Self::DistroUpgrade {
+                    kind: kind.into(),
+                    name,
+                    summary,
+                }
+            }
+            "ErrorCode" => {
+                let (code, details) = signal.body::<(u32, String)>()?;
+                Self::ErrorCode {
+                    code: code.into(),
+                    details,
+                }
+            }
+            "EulaRequired" => {
+                let (eula_id, package_id, vendor_name, license_agreement) =
+                    signal.body::<(String, String, String, String)>()?;
+                Self::EulaRequired {
+                    eula_id,
+                    package_id,
+                    vendor_name,
+                    license_agreement,
+                }
+            }
+            "Files" => {
+                let (package_id, files) = signal.body::<(String, Vec<String>)>()?;
+                Self::Files { package_id, files }
+            }
+            "Finished" => {
+                let (exit, runtime) = signal.body::<(u32, u32)>()?;
+                Self::Finished {
+                    exit: exit.into(),
+                    runtime,
+                }
+            }
+            "ItemProgress" => {
+                let (package_id, status, percentage) = signal.body::<(String, u32, u32)>()?;
+                Self::ItemProgress {
+                    package_id,
+                    status: status.into(),
+                    percentage,
+                }
+            }
+            "MediaChangeRequired" => {
+                let (media_type, media_id, media_text) = signal.body::<(u32, String, String)>()?;
+                Self::MediaChangeRequired {
+                    media_type: media_type.into(),
+                    media_id,
+                    media_text,
+                }
+            }
+            "Package" => {
+                let (info, package_id, summary) = signal.body::<(u32, String, String)>()?;
+                Self::Package(TransactionPackage {
+                    info: info.into(),
+                    package_id,
+                    summary,
+                })
+            }
+            "Packages" => {
+                let packages = signal.body::<Vec<(u32, String, String)>>()?;
+                return Ok(packages
+                    .into_iter()
+                    .map(|(info, package_id, summary)| {
+                        Self::Package(TransactionPackage {
+                            info: info.into(),
+                            package_id,
+                            summary,
+                        })
+                    })
+                    .collect());
+            }
+            "RepoDetail" => {
+                let (repo_id, description, enabled) = signal.body::<(String, String, bool)>()?;
+                Self::RepoDetail {
+                    repo_id,
+                    description,
+                    enabled,
+                }
+            }
+            "RepoSignatureRequired" => {
+                let (
+                    package_id,
+                    repository_name,
+                    key_url,
+                    key_userid,
+                    key_id,
+                    key_fingerprint,
+                    key_timestamp,
+                    sig_type,
+                ) = signal
+                    .body::<(String, String, String, String, String, String, String, u32)>()?;
+                Self::RepoSignatureRequired {
+                    package_id,
+                    repository_name,
+                    key_url,
+                    key_userid,
+                    key_id,
+                    key_fingerprint,
+                    key_timestamp,
+                    sig_type: sig_type.into(),
+                }
+            }
+            "RequireRestart" => {
+                let (restart, package_id) = signal.body::<(u32, String)>()?;
+                Self::RequireRestart {
+                    restart: restart.into(),
+                    package_id,
+                }
+            }
+            "Transaction" => {
+                let (object_path, timespec, succeeded, role, duration, data, uid, cmdline) = signal
+                    .body::<(
+                        zvariant::OwnedObjectPath,
+                        String,
+                        bool,
+                        u32,
+                        u32,
+                        String,
+                        u32,
+                        String,
+                    )>()?;
+                Self::Transaction {
+                    object_path: object_path.to_string(),
+                    timespec,
+                    succeeded,
+                    role: role.into(),
+                    duration,
+                    data,
+                    uid,
+                    cmdline,
+                }
+            }
+            "UpdateDetail" => Self::UpdateDetail(signal.body::<UpdateDetailBody>()?.into()),
+            "UpdateDetails" => {
+                let details = signal.body::<Vec<UpdateDetailBody>>()?;
+                return Ok(details
+                    .into_iter()
+                    .map(|detail| Self::UpdateDetail(detail.into()))
+                    .collect());
+            }
+            _ => {
+                eprintln!("ignoring unknown signal {member}");
+                return Ok(Vec::new());
+            }
+        };
+
+        Ok(vec![event])
+    }
+}
+
+fn details_from_map(map: &HashMap<String, zvariant::Value>) -> Option<TransactionDetails> {
+    // Backends leave out the keys they know nothing about, so only the package ID is required.
+    let get_string = |key: &str| match map.get(key) {
+        Some(zvariant::Value::Str(str)) => Some(str.to_string()),
+        _ => None,
+    };
+    let get_u64 = |key: &str| match map.get(key) {
+        Some(zvariant::Value::U64(value)) => *value,
+        _ => 0,
+    };
+
+    Some(TransactionDetails {
+        package_id: get_string("package-id")?,
+        summary: get_string("summary").unwrap_or_default(),
+        description: get_string("description").unwrap_or_default(),
+        url: get_string("url").unwrap_or_default(),
+        license: get_string("license").unwrap_or_default(),
+        size: get_u64("size"),
+        download_size: get_u64("download-size"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use packagekit_zbus::zbus::MessageBuilder;
+
+    const PATH: &str = "/1_abcdefgh";
+    const INTERFACE: &str = "org.freedesktop.PackageKit.Transaction";
+
+    fn signal<B>(member: &str, body: &B) -> Message
+    where
+        B: serde::Serialize + zvariant::DynamicType,
+    {
+        MessageBuilder::signal(PATH, INTERFACE, member)
+            .unwrap()
+            .build(body)
+            .unwrap()
+    }
+
+    fn decode_one(signal: &Message) -> TransactionEvent {
+        let mut events = TransactionEvent::from_signal(signal).unwrap();
+        assert_eq!(events.len(), 1, "expected a single event: {events:?}");
+        events.remove(0)
+    }
+
+    #[test]
+    fn details() {
+        let mut map = HashMap::new();
+        map.insert(
+            "package-id",
+            zvariant::Value::from("hello;2.10-3;amd64;local"),
+        );
+        map.insert("summary", zvariant::Value::from("example package"));
+        map.insert("license", zvariant::Value::from("GPL-3.0+"));
+        map.insert("size", zvariant::Value::from(57_348_000u64));
+        map.insert("download-size", zvariant::Value::from(1_024u64));
+
+        let TransactionEvent::Details(details) = decode_one(&signal("Details", &map)) else {
+            panic!("expected Details");
+        };
+        assert_eq!(details.package_id, "hello;2.10-3;amd64;local");
+        assert_eq!(details.summary, "example package");
+        assert_eq!(details.license, "GPL-3.0+");
+        assert_eq!(details.url, "");
+        assert_eq!(details.size, 57_348_000);
+        assert_eq!(details.download_size, 1_024);
+    }
+
+    #[test]
+    fn details_without_package_id_is_skipped() {
+        let mut map = HashMap::new();
+        map.insert("summary", zvariant::Value::from("orphan"));
+
+        let events = TransactionEvent::from_signal(&signal("Details", &map)).unwrap();
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn error_code() {
+        let body = (
+            13u32,
+            String::from("The following packages have unmet dependencies"),
+        );
+        match decode_one(&signal("ErrorCode", &body)) {
+            TransactionEvent::ErrorCode { code, details } => {
+                assert_eq!(code, Error::DepResolutionFailed);
+                assert_eq!(details, body.1);
+            }
+            event => panic!("unexpected event {event:?}"),
+        }
+    }
+
+    #[test]
+    fn item_progress() {
+        let body = ("hello;2.10-3;amd64;local", 9u32, 42u32);
+        match decode_one(&signal("ItemProgress", &body)) {
+            TransactionEvent::ItemProgress {
+                package_id,
+                status,
+                percentage,
+            } => {
+                assert_eq!(package_id, body.0);
+                assert_eq!(status, Status::Install);
+                assert_eq!(percentage, 42);
+            }
+            event => panic!("unexpected event {event:?}"),
+        }
+    }
+
+    #[test]
+    fn packages_are_flattened() {
+        let body = vec![
+            (12u32, "hello;2.10-3;amd64;local", "example package"),
+            (
+                13u32,
+                "goodbye;1.0-1;amd64;installed:debian",
+                "removed package",
+            ),
+        ];
+        let events = TransactionEvent::from_signal(&signal("Packages", &body)).unwrap();
+        let infos: Vec<_> = events
+            .iter()
+            .map(|event| match event {
+                TransactionEvent::Package(package) => package.info,
+                event => panic!("unexpected event {event:?}"),
+            })
+            .collect();
+        assert_eq!(infos, [Info::Installing, Info::Removing]);
+    }
+
+    #[test]
+    fn finished() {
+        match decode_one(&signal("Finished", &(3u32, 1500u32))) {
+            TransactionEvent::Finished { exit, runtime } => {
+                assert_eq!(exit, Exit::Cancelled);
+                assert_eq!(runtime, 1500);
+            }
+            event => panic!("unexpected event {event:?}"),
+        }
+    }
+
+    #[test]
+    fn require_restart() {
+        let body = (4u32, "linux-image;6.9;amd64;debian");
+        match decode_one(&signal("RequireRestart", &body)) {
+            TransactionEvent::RequireRestart {
+                restart,
+                package_id,
+            } => {
+                assert_eq!(restart, Restart::System);
+                assert_eq!(package_id, body.1);
+            }
+            event => panic!("unexpected event {event:?}"),
+        }
+    }
+
+    #[test]
+    fn repo_detail() {
+        let body = ("debian-backports", "Debian Backports", false);
+        match decode_one(&signal("RepoDetail", &body)) {
+            TransactionEvent::RepoDetail {
+                repo_id,
+                description,
+                enabled,
+            } => {
+                assert_eq!(repo_id, body.0);
+                assert_eq!(description, body.1);
+                assert!(!enabled);
+            }
+            event => panic!("unexpected event {event:?}"),
+        }
+    }
+
+    #[test]
+    fn unknown_codes_fall_back() {
+        let body = (9999u32, String::from("from the future"));
+        match decode_one(&signal("ErrorCode", &body)) {
+            TransactionEvent::ErrorCode { code, .. } => assert_eq!(code, Error::Unknown),
+            event => panic!("unexpected event {event:?}"),
+        }
+    }
+
+    #[test]
+    fn mismatched_body_is_an_error() {
+        assert!(TransactionEvent::from_signal(&signal("Finished", &("oops",))).is_err());
+    }
+}
@@ -0,0 +1,395 @@
+//! A stand-in for the PackageKit daemon, served on a private bus for tests.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+use zbus::{fdo, interface, zvariant, ObjectServer, SignalContext};
+
+use super::{Error, Exit, Info, Role, Status};
+
+/// A `dbus-daemon` running for the lifetime of this value.
+pub struct Bus {
+    daemon: Child,
+    address: String,
+}
+
+impl Bus {
+    /// Starts a private session bus, or returns `None` if `dbus-daemon` is not installed.
+    pub fn spawn() -> Option<Self> {
+        let mut daemon = Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .ok()?;
+
+        let mut address = String::new();
+        BufReader::new(daemon.stdout.take()?)
+            .read_line(&mut address)
+            .ok()?;
+
+        Some(Self {
+            daemon,
+            address: address.trim().to_string(),
+        })
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// Claims the PackageKit name on the bus and serves `script` until the service drops.
+    pub async fn serve(&self, script: Script) -> anyhow::Result<Service> {
+        let installs = Arc::<Mutex<Vec<Vec<String>>>>::default();
+        let packagekit = FakePackageKit {
+            script,
+            transactions: 0,
+            installs: installs.clone(),
+        };
+        let connection = zbus::connection::Builder::address(self.address())?
+            .name("org.freedesktop.PackageKit")?
+            .serve_at("/org/freedesktop/PackageKit", packagekit)?
+            .build()
+            .await?;
+
+        Ok(Service {
+            _connection: connection,
+            installs,
+        })
+    }
+}
+
+impl Drop for Bus {
+    fn drop(&mut self) {
+        let _ = self.daemon.kill();
+        let _ = self.daemon.wait();
+    }
+}
+
+/// A signal, or a change of a progress property, emitted by a fake transaction.
+#[derive(Clone, Debug)]
+pub enum Step {
+    Details {
+        package_id: &'static str,
+        summary: &'static str,
+        size: u64,
+        download_size: u64,
+    },
+    ItemProgress {
+        package_id: &'static str,
+        status: Status,
+        percentage: u32,
+    },
+    Package {
+        info: Info,
+        package_id: &'static str,
+        summary: &'static str,
+    },
+    Percentage(u32),
+    RepoDetail {
+        repo_id: &'static str,
+        description: &'static str,
+        enabled: bool,
+    },
+    Status(Status),
+    Speed(u32),
+    AllowCancel(bool),
+    ErrorCode(Error, &'static str),
+    Finished(Exit),
+}
+
+/// What each fake transaction does once its method is called.
+#[derive(Clone, Debug, Default)]
+pub struct Script {
+    pub get_details: Vec<Step>,
+    pub get_details_local: Vec<Step>,
+    pub get_repo_list: Vec<Step>,
+    pub install_files: Vec<Step>,
+    pub install_packages: Vec<Step>,
+    pub refresh_cache: Vec<Step>,
+    pub remove_packages: Vec<Step>,
+    pub repo_enable: Vec<Step>,
+    pub resolve: Vec<Step>,
+    /// Seconds since the cache was refreshed, or `None` if it never was.
+    pub time_since_refresh: Option<u32>,
+}
+
+/// The running fake daemon.
+pub struct Service {
+    _connection: zbus::Connection,
+    installs: Arc<Mutex<Vec<Vec<String>>>>,
+}
+
+impl Service {
+    /// The files passed to each `InstallFiles` call.
+    pub fn installs(&self) -> Vec<Vec<String>> {
+        self.installs.lock().unwrap().clone()
+    }
+}
+
+struct FakePackageKit {
+    script: Script,
+    transactions: u32,
+    installs: Arc<Mutex<Vec<Vec<String>>>>,
+}
+
+#[interface(name = "org.freedesktop.PackageKit")]
+impl FakePackageKit {
+    async fn create_transaction(
+        &mut self,
+        #[zbus(object_server)] server: &ObjectServer,
+    ) -> fdo::Result<zvariant::OwnedObjectPath> {
+        self.transactions += 1;
+        let path = format!("/{}_fake", self.transactions);
+        let transaction = FakeTransaction {
+            script: self.script.clone(),
+            installs: self.installs.clone(),
+            percentage: 101,
+            status: Status::Wait,
+            speed: 0,
+            allow_cancel: false,
+        };
+        server.at(path.as_str(), transaction).await?;
+
+        zvariant::OwnedObjectPath::try_from(path).map_err(|why| fdo::Error::Failed(why.to_string()))
+    }
+
+    fn get_time_since_action(&self, role: u32) -> u32 {
+        match Role::from(role) {
+            Role::RefreshCache => self.script.time_since_refresh.unwrap_or(u32::MAX),
+            _ => u32::MAX,
+        }
+    }
+}
+
+struct FakeTransaction {
+    script: Script,
+    installs: Arc<Mutex<Vec<Vec<String>>>>,
+    percentage: u32,
+    status: Status,
+    speed: u32,
+    allow_cancel: bool,
+}
+
+impl FakeTransaction {
+    async fn play(&mut self, ctxt: &SignalContext<'_>, steps: Vec<Step>) -> zbus::Result<()> {
+        for step in steps {
+            match step {
+                Step::Details {
+                    package_id,
+                    summary,
+                    size,
+                    download_size,
+                } => {
+                    let mut data = HashMap::new();
+                    data.insert("package-id", zvariant::Value::from(package_id));
+                    data.insert("summary", zvariant::Value::from(summary));
+                    data.insert("size", zvariant::Value::from(size));
+                    data.insert("download-size", zvariant::Value::from(download_size));
+                    Self::details(ctxt, data).await?;
+                }
+                Step::ItemProgress {
+                    package_id,
+                    status,
+                    percentage,
+                } => Self::item_progress(ctxt, package_id, status as u32, percentage).await?,
+                Step::Package {
+                    info,
+                    package_id,
+                    summary,
+                } => Self::package(ctxt, info as u32, package_id, summary).await?,
+                Step::Percentage(percentage) => {
+                    self.percentage = percentage;
+                    self.percentage_changed(ctxt).await?;
+                }
+                Step::RepoDetail {
+                    repo_id,
+                    description,
+                    enabled,
+                } => Self::repo_detail(ctxt, repo_id, description, enabled).await?,
+                Step::Status(status) => {
+                    self.status = status;
+                    self.status_changed(ctxt).await?;
+                }
+                Step::Speed(speed) => {
+                    self.speed = speed;
+                    self.speed_changed(ctxt).await?;
+                }
+                Step::AllowCancel(allow_cancel) => {
+                    self.allow_cancel = allow_cancel;
+                    self.allow_cancel_changed(ctxt).await?;
+                }
+                Step::ErrorCode(code, details) => {
+                    Self::error_code(ctxt, code as u32, details).await?
+                }
+                Step::Finished(exit) => Self::finished(ctxt, exit as u32, 0).await?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[interface(name = "org.freedesktop.PackageKit.Transaction")]
+impl FakeTransaction {
+    async fn set_hints(&self, _hints: Vec<String>) {}
+
+    async fn get_details(
+        &mut self,
+        _package_ids: Vec<String>,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> fdo::Result<()> {
+        let steps = self.script.get_details.clone();
+        Ok(self.play(&ctxt, steps).await?)
+    }
+
+    async fn get_details_local(
+        &mut self,
+        _files: Vec<String>,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> fdo::Result<()> {
+        let steps = self.script.get_details_local.clone();
+        Ok(self.play(&ctxt, steps).await?)
+    }
+
+    async fn get_repo_list(
+        &mut self,
+        _filter: u64,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> fdo::Result<()> {
+        let steps = self.script.get_repo_list.clone();
+        Ok(self.play(&ctxt, steps).await?)
+    }
+
+    async fn install_files(
+        &mut self,
+        _transaction_flags: u64,
+        full_paths: Vec<String>,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> fdo::Result<()> {
+        self.installs.lock().unwrap().push(full_paths);
+        let steps = self.script.install_files.clone();
+        Ok(self.play(&ctxt, steps).await?)
+    }
+
+    async fn install_packages(
+        &mut self,
+        _transaction_flags: u64,
+        _package_ids: Vec<String>,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> fdo::Result<()> {
+        let steps = self.script.install_packages.clone();
+        Ok(self.play(&ctxt, steps).await?)
+    }
+
+    async fn remove_packages(
+        &mut self,
+        _transaction_flags: u64,
+        _package_ids: Vec<String>,
+        _allow_deps: bool,
+        _autoremove: bool,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> fdo::Result<()> {
+        let steps = self.script.remove_packages.clone();
+        Ok(self.play(&ctxt, steps).await?)
+    }
+
+    async fn refresh_cache(
+        &mut self,
+        _force: bool,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> fdo::Result<()> {
+        let steps = self.script.refresh_cache.clone();
+        Ok(self.play(&ctxt, steps).await?)
+    }
+
+    async fn repo_enable(
+        &mut self,
+        _repo_id: String,
+        _enabled: bool,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> fdo::Result<()> {
+        let steps = self.script.repo_enable.clone();
+        Ok(self.play(&ctxt, steps).await?)
+    }
+
+    async fn resolve(
+        &mut self,
+        _filter: u64,
+        _packages: Vec<String>,
+        #[zbus(signal_context)] ctxt: SignalContext<'_>,
+    ) -> fdo::Result<()> {
+        let steps = self.script.resolve.clone();
+        Ok(self.play(&ctxt, steps).await?)
+    }
+
+    async fn cancel(&mut self, #[zbus(signal_context)] ctxt: SignalContext<'_>) -> fdo::Result<()> {
+        if !self.allow_cancel {
+            return Err(fdo::Error::Failed(String::from("cannot cancel")));
+        }
+        let steps = vec![
+            Step::ErrorCode(
+                Error::TransactionCancelled,
+                "The task was stopped successfully",
+            ),
+            Step::Finished(Exit::Cancelled),
+        ];
+        Ok(self.play(&ctxt, steps).await?)
+    }
+
+    #[zbus(property)]
+    fn percentage(&self) -> u32 {
+        self.percentage
+    }
+
+    #[zbus(property)]
+    fn status(&self) -> u32 {
+        self.status as u32
+    }
+
+    #[zbus(property)]
+    fn speed(&self) -> u32 {
+        self.speed
+    }
+
+    #[zbus(property)]
+    fn allow_cancel(&self) -> bool {
+        self.allow_cancel
+    }
+
+    #[zbus(signal)]
+    async fn details(
+        ctxt: &SignalContext<'_>,
+        data: HashMap<&str, zvariant::Value<'_>>,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn error_code(ctxt: &SignalContext<'_>, code: u32, details: &str) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn item_progress(
+        ctxt: &SignalContext<'_>,
+        id: &str,
+        status: u32,
+        percentage: u32,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn package(
+        ctxt: &SignalContext<'_>,
+        info: u32,
+        package_id: &str,
+        summary: &str,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn repo_detail(
+        ctxt: &SignalContext<'_>,
+        repo_id: &str,
+        description: &str,
+        enabled: bool,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn finished(ctxt: &SignalContext<'_>, exit: u32, runtime: u32) -> zbus::Result<()>;
+}
@@ -0,0 +1,84 @@
+//! PackageKit package IDs, `name;version;arch;data`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::bail;
+
+/// A parsed package ID.
+///
+/// `data` is where the package comes from: a repository ID, `installed`, or `local`
+/// for files.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PackageId {
+    pub name: String,
+    pub version: String,
+    pub arch: String,
+    pub data: String,
+}
+
+impl FromStr for PackageId {
+    type Err = anyhow::Error;
+
+    fn from_str(id: &str) -> anyhow::Result<Self> {
+        let parts: Vec<&str> = id.split(';').collect();
+        let [name, version, arch, data] = parts[..] else {
+            bail!(
+                "invalid package ID {id:?}: expected 4 fields, found {}",
+                parts.len()
+            );
+        };
+        if name.is_empty() {
+            bail!("invalid package ID {id:?}: the name is empty");
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            version: version.to_string(),
+            arch: arch.to_string(),
+            data: data.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for PackageId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{};{};{};{}",
+            self.name, self.version, self.arch, self.data
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips() {
+        let id: PackageId = "hello;2.10-3;amd64;local".parse().unwrap();
+        assert_eq!(id.name, "hello");
+        assert_eq!(id.version, "2.10-3");
+        assert_eq!(id.arch, "amd64");
+        assert_eq!(id.data, "local");
+        assert_eq!(id.to_string(), "hello;2.10-3;amd64;local");
+    }
+
+    #[test]
+    fn allows_empty_optional_fields() {
+        let id: PackageId = "hello;;;".parse().unwrap();
+        assert_eq!(id.name, "hello");
+        assert_eq!(id.version, "");
+    }
+
+    #[test]
+    fn rejects_malformed_ids() {
+        assert!("hello".parse::<PackageId>().is_err());
+        assert!("hello;2.10-3;amd64".parse::<PackageId>().is_err());
+        assert!("hello;2.10-3;amd64;local;extra"
+            .parse::<PackageId>()
+            .is_err());
+        assert!(";2.10-3;amd64;local".parse::<PackageId>().is_err());
+    }
+}
@@ -0,0 +1,43 @@
+//! Asking polkit whether the user may install packages, before PackageKit does.
+
+use std::collections::HashMap;
+
+use zbus_polkit::policykit1::{AuthorityProxy, Subject};
+
+/// The polkit action PackageKit checks for `InstallFiles` without the `OnlyTrusted` flag.
+const INSTALL_UNTRUSTED: &str = "org.freedesktop.packagekit.package-install-untrusted";
+
+/// Whether installing will succeed without the user proving they are an administrator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Authorization {
+    Authorized,
+    /// The user will be asked for their password, or an administrator's.
+    Challenge,
+    Denied,
+}
+
+/// Checks the install action for this process, without showing an authentication dialog.
+pub async fn check_install() -> anyhow::Result<Authorization> {
+    let connection = zbus::Connection::system().await?;
+    let authority = AuthorityProxy::new(&connection).await?;
+    let subject = Subject::new_for_owner(std::process::id(), None, None)?;
+
+    let result = authority
+        .check_authorization(
+            &subject,
+            INSTALL_UNTRUSTED,
+            &HashMap::new(),
+            // No flags: never pop up an authentication dialog just to check.
+            Default::default(),
+            "",
+        )
+        .await?;
+
+    Ok(if result.is_authorized {
+        Authorization::Authorized
+    } else if result.is_challenge {
+        Authorization::Challenge
+    } else {
+        Authorization::Denied
+    })
+}
@@ -0,0 +1,235 @@
+//! What an install would change on the system, found by simulating it first.
+
+use std::sync::Arc;
+
+use crate::backend::PackageBackend;
+use crate::package::{install_flags, repository_ids, Package};
+use crate::packagekit::{collect_packages, Filter, Info, PackageId, TransactionPackage};
+
+/// The packages a simulated install reported, grouped by what happens to them.
+#[derive(Clone, Debug, Default)]
+pub struct Preview {
+    pub install: Vec<PreviewPackage>,
+    pub upgrade: Vec<PreviewPackage>,
+    pub downgrade: Vec<PreviewPackage>,
+    pub remove: Vec<PreviewPackage>,
+    /// Bytes to download from the repositories, 0 if everything is local or cached.
+    pub download_size: u64,
+}
+
+/// A package touched by the install.
+#[derive(Clone, Debug)]
+pub struct PreviewPackage {
+    /// The version the package will have; for removals, the version that is removed.
+    pub id: PackageId,
+    pub summary: String,
+    /// The version replaced by an upgrade or downgrade.
+    pub installed_version: Option<String>,
+}
+
+impl PreviewPackage {
+    /// The repository the package comes from, or `local` for the selected files.
+    pub fn origin(&self) -> &str {
+        let data = &self.id.data;
+        data.strip_prefix("installed:").unwrap_or(data)
+    }
+}
+
+impl Preview {
+    /// Groups the `Package` events of a simulation by their `Info`.
+    pub fn new(packages: Vec<TransactionPackage>) -> Self {
+        let mut preview = Self::default();
+
+        for package in packages {
+            let group = match package.info {
+                Info::Installing | Info::Reinstalling => &mut preview.install,
+                Info::Updating => &mut preview.upgrade,
+                Info::Downgrading => &mut preview.downgrade,
+                Info::Removing | Info::Obsoleting => &mut preview.remove,
+                _ => continue,
+            };
+            let id = match package.package_id.parse::<PackageId>() {
+                Ok(id) => id,
+                Err(why) => {
+                    eprintln!("ignoring simulated package: {why}");
+                    continue;
+                }
+            };
+            // Backends may report a package more than once while resolving.
+            if group.iter().all(|known| known.id != id) {
+                group.push(PreviewPackage {
+                    id,
+                    summary: package.summary,
+                    installed_version: None,
+                });
+            }
+        }
+
+        preview
+    }
+
+    /// Whether the install changes nothing besides the selected files.
+    pub fn is_empty(&self) -> bool {
+        self.install
+            .iter()
+            .all(|package| package.origin() == "local")
+            && self.upgrade.is_empty()
+            && self.downgrade.is_empty()
+            && self.remove.is_empty()
+    }
+
+    /// Packages coming from the repositories, which may have to be downloaded.
+    fn downloads(&self) -> impl Iterator<Item = &PreviewPackage> {
+        self.install
+            .iter()
+            .chain(&self.upgrade)
+            .chain(&self.downgrade)
+            .filter(|package| package.origin() != "local")
+    }
+}
+
+/// Simulates installing the packages and reads what the real install would do.
+///
+/// Packages the user chose to take from the repositories are simulated by their package
+/// IDs, in a transaction of their own. Installed versions and download sizes are looked up
+/// afterwards; if that fails the preview is still returned without them.
+pub async fn simulate(
+    backend: Arc<dyn PackageBackend>,
+    packages: Vec<Package>,
+) -> anyhow::Result<Preview> {
+    let (repository, files): (Vec<Package>, Vec<Package>) = packages
+        .into_iter()
+        .partition(|package| package.repository_choice().is_some());
+
+    let mut simulated = Vec::new();
+    if !repository.is_empty() {
+        let events = backend
+            .simulate_packages(repository_ids(&repository))
+            .await?;
+        simulated.extend(collect_packages(events).await?);
+    }
+    if !files.is_empty() {
+        let paths = files.iter().map(|package| package.path.clone()).collect();
+        let events = backend.simulate(paths, install_flags(&files)).await?;
+        simulated.extend(collect_packages(events).await?);
+    }
+    let mut preview = Preview::new(simulated);
+
+    let replaced: Vec<String> = preview
+        .upgrade
+        .iter()
+        .chain(&preview.downgrade)
+        .map(|package| package.id.name.clone())
+        .collect();
+    if !replaced.is_empty() {
+        match backend.resolve(vec![Filter::Installed], replaced).await {
+            Ok(installed) => {
+                let installed: Vec<PackageId> = installed
+                    .iter()
+                    .filter_map(|package| package.package_id.parse().ok())
+                    .collect();
+                for package in preview.upgrade.iter_mut().chain(&mut preview.downgrade) {
+                    package.installed_version = installed
+                        .iter()
+                        .find(|id| id.name == package.id.name && id.arch == package.id.arch)
+                        .map(|id| id.version.clone());
+                }
+            }
+            Err(why) => eprintln!("failed to resolve replaced packages: {why:#}"),
+        }
+    }
+
+    let package_ids: Vec<String> = preview
+        .downloads()
+        .map(|package| package.id.to_string())
+        .collect();
+    if !package_ids.is_empty() {
+        match backend.package_details(package_ids).await {
+            Ok(details) => {
+                preview.download_size = details.iter().map(|details| details.download_size).sum();
+            }
+            Err(why) => eprintln!("failed to read download sizes: {why:#}"),
+        }
+    }
+
+    Ok(preview)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mock::MockBackend;
+    use crate::packagekit::TransactionDetails;
+
+    fn package(info: Info, package_id: &str) -> TransactionPackage {
+        TransactionPackage {
+            info,
+            package_id: package_id.to_string(),
+            summary: String::new(),
+        }
+    }
+
+    #[test]
+    fn groups_packages_by_info() {
+        let preview = Preview::new(vec![
+            package(Info::Installing, "hello;2.10-3;amd64;local"),
+            package(Info::Installing, "libfoo1;1.0-1;amd64;debian-stable-main"),
+            package(Info::Installing, "libfoo1;1.0-1;amd64;debian-stable-main"),
+            package(Info::Updating, "libc6;2.36-9;amd64;debian-stable-main"),
+            package(
+                Info::Removing,
+                "hello-traditional;2.10-5;amd64;installed:debian-stable-main",
+            ),
+            package(Info::Finished, "hello;2.10-3;amd64;local"),
+        ]);
+
+        assert_eq!(preview.install.len(), 2);
+        assert_eq!(preview.upgrade[0].id.name, "libc6");
+        assert_eq!(preview.remove[0].origin(), "debian-stable-main");
+        assert!(!preview.is_empty());
+
+        let preview = Preview::new(vec![package(Info::Installing, "hello;2.10-3;amd64;local")]);
+        assert!(preview.is_empty());
+    }
+
+    #[tokio::test]
+    async fn reads_replaced_versions_and_download_size() {
+        let backend: Arc<dyn PackageBackend> = Arc::new(
+            MockBackend::new()
+                .with_simulated(Info::Installing, "hello;2.10-3;amd64;local")
+                .with_simulated(Info::Updating, "libc6;2.36-9;amd64;debian-stable-main")
+                .with_installed("libc6;2.36-8;amd64;installed:debian-stable-main")
+                .with_package_details(TransactionDetails {
+                    package_id: String::from("libc6;2.36-9;amd64;debian-stable-main"),
+                    summary: String::new(),
+                    description: String::new(),
+                    url: String::new(),
+                    license: String::new(),
+                    size: 12_988_000,
+                    download_size: 2_757_000,
+                }),
+        );
+
+        let hello = Package::new(
+            String::from("/tmp/hello.deb"),
+            TransactionDetails {
+                package_id: String::from("hello;2.10-3;amd64;local"),
+                summary: String::new(),
+                description: String::new(),
+                url: String::new(),
+                license: String::new(),
+                size: 57_348,
+                download_size: 0,
+            },
+        )
+        .unwrap();
+
+        let preview = simulate(backend, vec![hello]).await.unwrap();
+        assert_eq!(preview.install.len(), 1);
+        assert_eq!(
+            preview.upgrade[0].installed_version.as_deref(),
+            Some("2.36-8")
+        );
+        assert_eq!(preview.download_size, 2_757_000);
+    }
+}
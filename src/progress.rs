@@ -0,0 +1,162 @@
+//! The state of a running install, as shown in the progress panel.
+
+use crate::fl;
+use crate::packagekit::{Status, TransactionEvent};
+
+/// Progress of a running transaction, updated from its events.
+#[derive(Clone, Debug, Default)]
+pub struct Progress {
+    /// What the transaction as a whole is doing.
+    pub status: Status,
+    /// The overall percentage, or `None` while PackageKit does not know it.
+    pub percentage: Option<u32>,
+    /// One entry per package, in the order PackageKit first reported them.
+    pub items: Vec<ItemProgress>,
+    /// Download speed in bytes per second.
+    pub speed: u32,
+    /// Seconds since the transaction started.
+    pub elapsed_time: u32,
+    /// Estimated seconds left, or 0 if unknown.
+    pub remaining_time: u32,
+}
+
+/// Progress of a single package within a transaction.
+#[derive(Clone, Debug)]
+pub struct ItemProgress {
+    pub package_id: String,
+    pub status: Status,
+    pub percentage: Option<u32>,
+}
+
+impl ItemProgress {
+    /// The package name, taken from its package ID.
+    pub fn name(&self) -> &str {
+        self.package_id.split(';').next().unwrap_or_default()
+    }
+}
+
+impl Progress {
+    /// Applies an event; events that are not about progress are ignored.
+    pub fn update(&mut self, event: &TransactionEvent) {
+        match event {
+            TransactionEvent::Status(status) => self.status = *status,
+            // PackageKit reports 101 while the percentage is unknown; keep the last value
+            // instead of jumping back to zero.
+            TransactionEvent::Percentage(percentage) if *percentage <= 100 => {
+                self.percentage = Some(*percentage);
+            }
+            TransactionEvent::Speed(speed) => self.speed = *speed,
+            TransactionEvent::ElapsedTime(seconds) => self.elapsed_time = *seconds,
+            TransactionEvent::RemainingTime(seconds) => self.remaining_time = *seconds,
+            TransactionEvent::ItemProgress {
+                package_id,
+                status,
+                percentage,
+            } => {
+                let percentage = (*percentage <= 100).then_some(*percentage);
+                match self
+                    .items
+                    .iter_mut()
+                    .find(|item| item.package_id == *package_id)
+                {
+                    Some(item) => {
+                        item.status = *status;
+                        item.percentage = percentage.or(item.percentage);
+                    }
+                    None => self.items.push(ItemProgress {
+                        package_id: package_id.clone(),
+                        status: *status,
+                        percentage,
+                    }),
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// A short, localized description of a status.
+pub fn status_text(status: Status) -> String {
+    match status {
+        Status::Wait | Status::WaitingForLock => fl!("status-waiting"),
+        Status::WaitingForAuth => fl!("status-waiting-for-auth"),
+        Status::Setup | Status::Request | Status::Query | Status::Info => fl!("status-setup"),
+        Status::RefreshCache
+        | Status::LoadingCache
+        | Status::DownloadRepository
+        | Status::DownloadPackagelist
+        | Status::DownloadFilelist
+        | Status::DownloadChangelog
+        | Status::DownloadGroup
+        | Status::DownloadUpdateinfo
+        | Status::GeneratePackageList => fl!("status-loading-cache"),
+        Status::DepResolve => fl!("status-dep-resolve"),
+        Status::Download => fl!("status-download"),
+        Status::SigCheck | Status::TestCommit => fl!("status-checking"),
+        Status::Install | Status::Commit | Status::CopyFiles => fl!("status-install"),
+        Status::Update => fl!("status-update"),
+        Status::Remove | Status::Obsolete => fl!("status-remove"),
+        Status::Cleanup => fl!("status-cleanup"),
+        Status::RunHook
+        | Status::ScanApplications
+        | Status::ScanProcessList
+        | Status::CheckExecutableFiles
+        | Status::CheckLibraries
+        | Status::Repackaging => fl!("status-run-hook"),
+        Status::Cancel => fl!("status-cancel"),
+        Status::Finished => fl!("status-finished"),
+        Status::Unknown | Status::Running => fl!("status-running"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HELLO: &str = "hello;2.10-3;amd64;local";
+    const WORLD: &str = "world;1.0-1;all;local";
+
+    fn item(package_id: &str, status: Status, percentage: u32) -> TransactionEvent {
+        TransactionEvent::ItemProgress {
+            package_id: package_id.to_string(),
+            status,
+            percentage,
+        }
+    }
+
+    #[test]
+    fn unknown_percentage_keeps_last_value() {
+        let mut progress = Progress::default();
+        progress.update(&TransactionEvent::Percentage(101));
+        assert_eq!(progress.percentage, None);
+
+        progress.update(&TransactionEvent::Percentage(40));
+        progress.update(&TransactionEvent::Percentage(101));
+        assert_eq!(progress.percentage, Some(40));
+    }
+
+    #[test]
+    fn items_are_tracked_per_package() {
+        let mut progress = Progress::default();
+        progress.update(&item(HELLO, Status::Download, 100));
+        progress.update(&item(WORLD, Status::Download, 30));
+        progress.update(&item(HELLO, Status::Install, 101));
+
+        assert_eq!(progress.items.len(), 2);
+        assert_eq!(progress.items[0].name(), "hello");
+        assert_eq!(progress.items[0].status, Status::Install);
+        assert_eq!(progress.items[0].percentage, Some(100));
+        assert_eq!(progress.items[1].name(), "world");
+        assert_eq!(progress.items[1].percentage, Some(30));
+    }
+
+    #[test]
+    fn other_events_are_ignored() {
+        let mut progress = Progress::default();
+        progress.update(&TransactionEvent::Speed(1_000));
+        progress.update(&TransactionEvent::AllowCancel(true));
+        assert_eq!(progress.speed, 1_000);
+        assert_eq!(progress.percentage, None);
+        assert!(progress.items.is_empty());
+    }
+}
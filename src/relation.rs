@@ -0,0 +1,209 @@
+//! Relationship fields such as `Depends` and `Provides`.
+//!
+//! https://www.debian.org/doc/debian-policy/ch-relationships.html
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use anyhow::bail;
+
+use crate::version;
+
+/// A version comparison in a relation, e.g. the `>=` of `libc6 (>= 2.34)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Op {
+    Earlier,
+    EarlierEqual,
+    Equal,
+    LaterEqual,
+    Later,
+}
+
+impl Op {
+    fn parse(op: &str) -> anyhow::Result<Self> {
+        Ok(match op {
+            "<<" => Op::Earlier,
+            // `<` and `>` are obsolete spellings of `<=` and `>=`.
+            "<=" | "<" => Op::EarlierEqual,
+            "=" => Op::Equal,
+            ">=" | ">" => Op::LaterEqual,
+            ">>" => Op::Later,
+            _ => bail!("unknown version operator {op:?}"),
+        })
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Op::Earlier => "<<",
+            Op::EarlierEqual => "<=",
+            Op::Equal => "=",
+            Op::LaterEqual => ">=",
+            Op::Later => ">>",
+        }
+    }
+
+    fn allows(self, ordering: Ordering) -> bool {
+        match self {
+            Op::Earlier => ordering == Ordering::Less,
+            Op::EarlierEqual => ordering != Ordering::Greater,
+            Op::Equal => ordering == Ordering::Equal,
+            Op::LaterEqual => ordering != Ordering::Less,
+            Op::Later => ordering == Ordering::Greater,
+        }
+    }
+}
+
+/// A single package named in a relationship field, with an optional version constraint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Relation {
+    pub name: String,
+    /// The architecture qualifier, such as `any` in `python3:any`.
+    pub arch: Option<String>,
+    pub constraint: Option<(Op, String)>,
+}
+
+impl Relation {
+    /// Parses one alternative, e.g. `libc6 (>= 2.34)`.
+    ///
+    /// Architecture restrictions (`[amd64]`) and build profiles (`<!nocheck>`) only occur
+    /// in source packages and are ignored.
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let text = text.trim();
+        let end = text
+            .find(|c: char| c.is_whitespace() || matches!(c, '(' | '[' | '<'))
+            .unwrap_or(text.len());
+        let (name, rest) = text.split_at(end);
+        let (name, arch) = match name.split_once(':') {
+            Some((name, arch)) => (name, Some(arch.to_string())),
+            None => (name, None),
+        };
+        if name.is_empty() {
+            bail!("relation {text:?} has no package name");
+        }
+
+        let rest = rest.trim_start();
+        let constraint = match rest.strip_prefix('(') {
+            Some(rest) => {
+                let Some((constraint, _)) = rest.split_once(')') else {
+                    bail!("unclosed version constraint in {text:?}");
+                };
+                let constraint = constraint.trim();
+                let split = constraint
+                    .find(|c: char| !matches!(c, '<' | '=' | '>'))
+                    .unwrap_or(constraint.len());
+                let (op, version) = constraint.split_at(split);
+                let version = version.trim();
+                if version.is_empty() {
+                    bail!("version constraint in {text:?} has no version");
+                }
+                Some((Op::parse(op.trim())?, version.to_string()))
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            arch,
+            constraint,
+        })
+    }
+
+    /// Whether `version` of a package with this name meets the constraint.
+    ///
+    /// Versions that cannot be compared do not meet any constraint.
+    pub fn allows(&self, version: &str) -> bool {
+        match &self.constraint {
+            None => true,
+            Some((op, wanted)) => {
+                version::compare(version, wanted).is_ok_and(|ordering| op.allows(ordering))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Relation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+        if let Some(arch) = &self.arch {
+            write!(f, ":{arch}")?;
+        }
+        if let Some((op, version)) = &self.constraint {
+            write!(f, " ({} {version})", op.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a relationship field into groups that must all be met, each listing
+/// alternatives of which one is enough: `a, b | c` is `[[a], [b, c]]`.
+pub fn parse(field: &str) -> anyhow::Result<Vec<Vec<Relation>>> {
+    field
+        .split(',')
+        .filter(|group| !group.trim().is_empty())
+        .map(|group| group.split('|').map(Relation::parse).collect())
+        .collect()
+}
+
+/// Formats a group of alternatives the way it is written in the control file.
+pub fn format_group(group: &[Relation]) -> String {
+    group
+        .iter()
+        .map(Relation::to_string)
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_groups_and_alternatives() {
+        let groups =
+            parse("libc6 (>= 2.34), default-mta | mail-transport-agent,\n python3:any (<< 3.13~)")
+                .unwrap();
+
+        assert_eq!(groups.len(), 3);
+        assert_eq!(
+            groups[0][0].constraint,
+            Some((Op::LaterEqual, String::from("2.34")))
+        );
+        assert_eq!(groups[1][1].name, "mail-transport-agent");
+        assert_eq!(groups[2][0].arch.as_deref(), Some("any"));
+        assert_eq!(format_group(&groups[2]), "python3:any (<< 3.13~)");
+        assert_eq!(
+            format_group(&groups[1]),
+            "default-mta | mail-transport-agent"
+        );
+
+        assert!(parse("").unwrap().is_empty());
+        assert_eq!(parse("foo [amd64] <!nocheck>").unwrap()[0][0].name, "foo");
+        assert_eq!(
+            parse("foo(>=1.0)").unwrap()[0][0].constraint,
+            Some((Op::LaterEqual, String::from("1.0")))
+        );
+    }
+
+    #[test]
+    fn rejects_broken_relations() {
+        assert!(parse("(>= 1.0)").is_err());
+        assert!(parse("foo (>= 1.0").is_err());
+        assert!(parse("foo (>=)").is_err());
+        assert!(parse("foo (~ 1.0)").is_err());
+    }
+
+    #[test]
+    fn checks_constraints() {
+        let relation = Relation::parse("libapp (>= 1.2-1)").unwrap();
+        assert!(relation.allows("1.2-1"));
+        assert!(relation.allows("1:0.1"));
+        assert!(!relation.allows("1.2~rc1-1"));
+
+        let relation = Relation::parse("libapp (<< 2)").unwrap();
+        assert!(relation.allows("1.9"));
+        assert!(!relation.allows("2"));
+        assert!(!relation.allows("not a version"));
+
+        assert!(Relation::parse("libapp").unwrap().allows(""));
+    }
+}
@@ -0,0 +1,163 @@
+//! How the selected files depend on each other.
+
+use crate::package::Package;
+use crate::relation::{self, Relation};
+
+/// Where a dependency of a selected package comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Provider {
+    /// A selected package, by its index, satisfies it.
+    Selected(usize),
+    /// A selected package has the name but not a version the dependency accepts.
+    Mismatched(usize),
+    /// No selected package has the name, so it must be installed or in a repository.
+    System,
+}
+
+/// A `Depends` or `Pre-Depends` group of a selected package.
+#[derive(Clone, Debug)]
+pub struct Dependency {
+    /// The index of the package that has the dependency.
+    pub package: usize,
+    /// The group as written in the control file, e.g. `libapp1 (>= 1.2) | libapp-dev`.
+    pub relation: String,
+    pub provider: Provider,
+}
+
+/// Whether a package meets a relation through its name or its `Provides`.
+///
+/// Returns `None` when the package is unrelated, `Some(false)` when the name matches but
+/// the version does not.
+fn meets(package: &Package, relation: &Relation) -> Option<bool> {
+    if package.id.name == relation.name {
+        return Some(relation.allows(&package.id.version));
+    }
+
+    let provides = relation::parse(&package.provides).unwrap_or_default();
+    let provided = provides
+        .iter()
+        .flatten()
+        .filter(|provided| provided.name == relation.name)
+        .collect::<Vec<_>>();
+    if provided.is_empty() {
+        return None;
+    }
+    // Unversioned provides never satisfy a versioned dependency.
+    Some(provided.iter().any(|provided| match &provided.constraint {
+        Some((_, version)) => relation.allows(version),
+        None => relation.constraint.is_none(),
+    }))
+}
+
+/// Works out, for each dependency of the packages, whether another one of them provides it.
+pub fn dependencies(packages: &[Package]) -> Vec<Dependency> {
+    let mut dependencies = Vec::new();
+
+    for (index, package) in packages.iter().enumerate() {
+        for field in [&package.pre_depends, &package.depends] {
+            let groups = match relation::parse(field) {
+                Ok(groups) => groups,
+                Err(why) => {
+                    eprintln!("ignoring dependencies of {}: {why}", package.id.name);
+                    continue;
+                }
+            };
+
+            for group in groups {
+                let mut provider = Provider::System;
+                'search: for relation in &group {
+                    for (other, candidate) in packages.iter().enumerate() {
+                        if other == index {
+                            continue;
+                        }
+                        match meets(candidate, relation) {
+                            Some(true) => {
+                                provider = Provider::Selected(other);
+                                break 'search;
+                            }
+                            Some(false) if provider == Provider::System => {
+                                provider = Provider::Mismatched(other);
+                            }
+                            _ => (),
+                        }
+                    }
+                }
+
+                dependencies.push(Dependency {
+                    package: index,
+                    relation: relation::format_group(&group),
+                    provider,
+                });
+            }
+        }
+    }
+
+    dependencies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packagekit::TransactionDetails;
+
+    fn package(id: &str, depends: &str, provides: &str) -> Package {
+        let details = TransactionDetails {
+            package_id: id.to_string(),
+            summary: String::new(),
+            description: String::new(),
+            url: String::new(),
+            license: String::new(),
+            size: 0,
+            download_size: 0,
+        };
+        let name = id.split(';').next().unwrap();
+        let mut package = Package::new(format!("/tmp/{name}.deb"), details).unwrap();
+        package.depends = depends.to_string();
+        package.provides = provides.to_string();
+        package
+    }
+
+    #[test]
+    fn finds_providers_among_selection() {
+        let packages = [
+            package(
+                "app;1.2-1;amd64;local",
+                "libc6, libapp1 (>= 1.2), app-data",
+                "",
+            ),
+            package("libapp1;1.2-1;amd64;local", "", ""),
+            package("app-data-v2;2.0-1;all;local", "", "app-data (= 2.0)"),
+            package("plugin;1.0-1;amd64;local", "app (>= 2.0)", ""),
+        ];
+        let dependencies = dependencies(&packages);
+
+        let providers: Vec<(usize, &str, Provider)> = dependencies
+            .iter()
+            .map(|dependency| {
+                (
+                    dependency.package,
+                    dependency.relation.as_str(),
+                    dependency.provider,
+                )
+            })
+            .collect();
+        assert_eq!(
+            providers,
+            [
+                (0, "libc6", Provider::System),
+                (0, "libapp1 (>= 1.2)", Provider::Selected(1)),
+                (0, "app-data", Provider::Selected(2)),
+                (3, "app (>= 2.0)", Provider::Mismatched(0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn unversioned_provides_do_not_satisfy_versions() {
+        let packages = [
+            package("app;1.0;amd64;local", "mta (>= 1)", ""),
+            package("postfix;3.7;amd64;local", "", "mta"),
+        ];
+        assert_eq!(dependencies(&packages)[0].provider, Provider::Mismatched(1));
+    }
+}
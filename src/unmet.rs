@@ -0,0 +1,272 @@
+//! Explaining a failed dependency resolution, and what could fix it.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::backend::PackageBackend;
+use crate::packagekit::Repository;
+use crate::relation::{self, Relation};
+
+/// A dependency that could not be met, read from the `dep-resolution-failed` details.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Unmet {
+    /// The package that has the dependency.
+    pub package: String,
+    /// Any one of these would do.
+    pub alternatives: Vec<Relation>,
+    /// Why it cannot be met, e.g. "it is not installable".
+    pub reason: String,
+}
+
+impl Unmet {
+    /// The dependency as written in the control file.
+    pub fn relation(&self) -> String {
+        relation::format_group(&self.alternatives)
+    }
+}
+
+/// Reads the unmet dependencies from apt's report, which lists one per line:
+///
+/// ```text
+/// The following packages have unmet dependencies:
+///   hello: Depends: libc6 (>= 9.0) but 2.36-9 is to be installed
+///   app: Depends: libapp1 but it is not installable or
+///                 libapp2 but it is not installable
+/// ```
+///
+/// Lines that do not look like this are skipped.
+pub fn parse(details: &str) -> Vec<Unmet> {
+    const KINDS: [&str; 3] = [" Depends: ", " PreDepends: ", " Pre-Depends: "];
+
+    let mut unmet: Vec<Unmet> = Vec::new();
+    let mut continues = false;
+
+    for line in details.lines() {
+        let line = line.trim();
+        let found = KINDS
+            .iter()
+            .find_map(|kind| line.find(kind).map(|at| (at, kind.len())));
+
+        let (package, rest) = match found {
+            Some((at, len)) => (Some(&line[..at]), &line[at + len..]),
+            None if continues => (None, line),
+            None => continue,
+        };
+
+        let (rest, or) = match rest.strip_suffix(" or") {
+            Some(rest) => (rest, true),
+            None => (rest, false),
+        };
+        let (text, reason) = rest.split_once(" but ").unwrap_or((rest, ""));
+        continues = or;
+
+        let Ok(alternative) = Relation::parse(text) else {
+            continue;
+        };
+        match package {
+            Some(package) => {
+                // apt may qualify the package with its architecture, e.g. `hello:amd64:`.
+                let package = package.trim_end_matches(':');
+                let package = package.split(':').next().unwrap_or(package);
+                unmet.push(Unmet {
+                    package: package.to_string(),
+                    alternatives: vec![alternative],
+                    reason: reason.to_string(),
+                });
+            }
+            None => {
+                if let Some(last) = unmet.last_mut() {
+                    last.alternatives.push(alternative);
+                }
+            }
+        }
+    }
+
+    unmet
+}
+
+/// A `.deb` next to a selected file that would meet an unmet dependency.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Candidate {
+    pub path: String,
+    pub name: String,
+    pub version: String,
+}
+
+/// Looks for installers of the unmet dependencies in the directories of the selected files.
+///
+/// Files are matched by their name, `name_version_arch.deb`, without being opened. Epochs
+/// are written `%3a` in file names, as dpkg-name does, instead of `:`.
+pub fn find_candidates(unmet: &[Unmet], selected: &[String]) -> Vec<Candidate> {
+    let directories: BTreeSet<&Path> = selected
+        .iter()
+        .filter_map(|path| Path::new(path).parent())
+        .collect();
+
+    let mut candidates = Vec::new();
+    for directory in directories {
+        let Ok(entries) = std::fs::read_dir(directory) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path().to_string_lossy().into_owned();
+            if selected.contains(&path) || candidates.iter().any(|c: &Candidate| c.path == path) {
+                continue;
+            }
+            let Some(stem) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_suffix(".deb"))
+                .map(str::to_string)
+            else {
+                continue;
+            };
+            let mut parts = stem.split('_');
+            let (Some(name), Some(version)) = (parts.next(), parts.next()) else {
+                continue;
+            };
+            let version = version.replace("%3a", ":").replace("%3A", ":");
+            let wanted = unmet
+                .iter()
+                .flat_map(|unmet| &unmet.alternatives)
+                .any(|relation| relation.name == name && relation.allows(&version));
+            if wanted {
+                candidates.push(Candidate {
+                    path,
+                    name: name.to_string(),
+                    version,
+                });
+            }
+        }
+    }
+
+    candidates.sort_by(|a, b| a.path.cmp(&b.path));
+    candidates
+}
+
+/// What is known about a failed dependency resolution.
+#[derive(Clone, Debug, Default)]
+pub struct Diagnosis {
+    pub unmet: Vec<Unmet>,
+    /// Software sources that are configured but disabled.
+    ///
+    /// Their packages are not known while they are disabled, so they are not checked for
+    /// what is missing and may not provide it.
+    pub disabled: Vec<Repository>,
+    pub candidates: Vec<Candidate>,
+}
+
+/// Parses the failure, looks for local files that meet it and lists the disabled sources.
+pub async fn diagnose(
+    backend: Arc<dyn PackageBackend>,
+    details: String,
+    selected: Vec<String>,
+) -> Diagnosis {
+    let unmet = parse(&details);
+    if unmet.is_empty() {
+        return Diagnosis::default();
+    }
+
+    let disabled = match backend.repositories().await {
+        Ok(repositories) => repositories
+            .into_iter()
+            .filter(|repository| !repository.enabled)
+            .collect(),
+        Err(why) => {
+            eprintln!("failed to list software sources: {why:#}");
+            Vec::new()
+        }
+    };
+
+    let search = unmet.clone();
+    let candidates = tokio::task::spawn_blocking(move || find_candidates(&search, &selected))
+        .await
+        .unwrap_or_default();
+
+    Diagnosis {
+        unmet,
+        disabled,
+        candidates,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::mock::MockBackend;
+
+    const REPORT: &str = "\
+The following packages have unmet dependencies:
+  hello: Depends: libc6 (>= 9.0) but 2.36-9 is to be installed
+  app:amd64: Depends: libapp1 (>= 1.2) but it is not installable or
+                      libapp2 but it is not installable
+  app:amd64: PreDepends: dpkg (>= 1.22)
+";
+
+    #[test]
+    fn parses_apt_report() {
+        let unmet = parse(REPORT);
+
+        assert_eq!(unmet.len(), 3);
+        assert_eq!(unmet[0].package, "hello");
+        assert_eq!(unmet[0].relation(), "libc6 (>= 9.0)");
+        assert_eq!(unmet[0].reason, "2.36-9 is to be installed");
+        assert_eq!(unmet[1].package, "app");
+        assert_eq!(unmet[1].relation(), "libapp1 (>= 1.2) | libapp2");
+        assert_eq!(unmet[2].relation(), "dpkg (>= 1.22)");
+        assert_eq!(unmet[2].reason, "");
+
+        assert!(parse("E: Unable to locate package hello").is_empty());
+    }
+
+    #[tokio::test]
+    async fn finds_sources_and_local_files() {
+        let dir = std::env::temp_dir().join(format!("wizard-unmet-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in [
+            "app_1.0_amd64.deb",
+            "libapp1_1.1_amd64.deb",
+            "libapp1_1.2-1_amd64.deb",
+            "libapp2_1%3a0.9_amd64.deb",
+            "libapp2_2.0_amd64.tar",
+        ] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+        let app = dir.join("app_1.0_amd64.deb").to_string_lossy().into_owned();
+        let other = dir.join("other.deb").to_string_lossy().into_owned();
+
+        let backend = MockBackend::new()
+            .with_repository("debian-stable-main", true)
+            .with_repository("debian-stable-backports-main", false);
+        let selected = vec![app.clone(), other, app];
+        let diagnosis = diagnose(Arc::new(backend), REPORT.to_string(), selected).await;
+
+        assert_eq!(diagnosis.unmet.len(), 3);
+        assert_eq!(diagnosis.disabled.len(), 1);
+        assert_eq!(diagnosis.disabled[0].id, "debian-stable-backports-main");
+        assert_eq!(
+            diagnosis.candidates,
+            [
+                Candidate {
+                    path: dir
+                        .join("libapp1_1.2-1_amd64.deb")
+                        .to_string_lossy()
+                        .into_owned(),
+                    name: String::from("libapp1"),
+                    version: String::from("1.2-1"),
+                },
+                Candidate {
+                    path: dir
+                        .join("libapp2_1%3a0.9_amd64.deb")
+                        .to_string_lossy()
+                        .into_owned(),
+                    name: String::from("libapp2"),
+                    version: String::from("1:0.9"),
+                },
+            ]
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
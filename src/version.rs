@@ -0,0 +1,294 @@
+//! Debian package versions, compared the way dpkg does.
+//!
+//! https://www.debian.org/doc/debian-policy/ch-controlfields.html#version
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::bail;
+
+/// A version of the form `[epoch:]upstream_version[-debian_revision]`.
+///
+/// Like dpkg, parsing only rejects versions whose structure is broken; unusual
+/// characters are accepted and compared by their byte value.
+#[derive(Debug, Clone)]
+pub struct Version {
+    pub epoch: u32,
+    pub upstream: String,
+    pub revision: String,
+}
+
+impl FromStr for Version {
+    type Err = anyhow::Error;
+
+    fn from_str(version: &str) -> anyhow::Result<Self> {
+        let version = version.trim();
+        if version.is_empty() {
+            bail!("version string is empty");
+        }
+        if version.contains(char::is_whitespace) {
+            bail!("version string {version:?} has embedded spaces");
+        }
+
+        let (epoch, rest) = match version.split_once(':') {
+            Some((epoch, rest)) => {
+                if epoch.is_empty() || !epoch.bytes().all(|byte| byte.is_ascii_digit()) {
+                    bail!("epoch in version {version:?} is not a number");
+                }
+                let Ok(epoch) = epoch.parse::<u32>() else {
+                    bail!("epoch in version {version:?} is too big");
+                };
+                if rest.is_empty() {
+                    bail!("nothing after colon in version {version:?}");
+                }
+                (epoch, rest)
+            }
+            None => (0, version),
+        };
+
+        let (upstream, revision) = match rest.rsplit_once('-') {
+            Some((_, "")) => bail!("revision in version {version:?} is empty"),
+            Some((upstream, revision)) => (upstream, revision),
+            None => (rest, ""),
+        };
+        if upstream.is_empty() {
+            bail!("upstream version in {version:?} is empty");
+        }
+
+        Ok(Self {
+            epoch,
+            upstream: upstream.to_string(),
+            revision: revision.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.epoch > 0 {
+            write!(f, "{}:", self.epoch)?;
+        }
+        f.write_str(&self.upstream)?;
+        if !self.revision.is_empty() {
+            write!(f, "-{}", self.revision)?;
+        }
+        Ok(())
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.epoch
+            .cmp(&other.epoch)
+            .then_with(|| verrevcmp(&self.upstream, &other.upstream))
+            .then_with(|| verrevcmp(&self.revision, &other.revision))
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Versions are equal when dpkg considers them equal, so "1.0" equals "0:1.0-0".
+impl PartialEq for Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Version {}
+
+/// Compares two version strings.
+pub fn compare(a: &str, b: &str) -> anyhow::Result<Ordering> {
+    Ok(a.parse::<Version>()?.cmp(&b.parse::<Version>()?))
+}
+
+/// The sort weight of a non-digit byte: `~` before the end of the string, which
+/// comes before letters, which come before everything else.
+fn order(byte: Option<u8>) -> i32 {
+    match byte {
+        None => 0,
+        Some(byte) if byte.is_ascii_digit() => 0,
+        Some(byte) if byte.is_ascii_alphabetic() => i32::from(byte),
+        Some(b'~') => -1,
+        Some(byte) => i32::from(byte) + 256,
+    }
+}
+
+/// dpkg's comparison of an upstream version or revision: alternating runs of
+/// non-digits, compared by [`order`], and digits, compared numerically.
+fn verrevcmp(a: &str, b: &str) -> Ordering {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let (mut i, mut j) = (0, 0);
+    let is_digit = |bytes: &[u8], at: usize| bytes.get(at).is_some_and(u8::is_ascii_digit);
+
+    while i < a.len() || j < b.len() {
+        while (i < a.len() && !is_digit(a, i)) || (j < b.len() && !is_digit(b, j)) {
+            let (ac, bc) = (order(a.get(i).copied()), order(b.get(j).copied()));
+            if ac != bc {
+                return ac.cmp(&bc);
+            }
+            i += 1;
+            j += 1;
+        }
+
+        while a.get(i) == Some(&b'0') {
+            i += 1;
+        }
+        while b.get(j) == Some(&b'0') {
+            j += 1;
+        }
+
+        let mut first_diff = Ordering::Equal;
+        while is_digit(a, i) && is_digit(b, j) {
+            if first_diff == Ordering::Equal {
+                first_diff = a[i].cmp(&b[j]);
+            }
+            i += 1;
+            j += 1;
+        }
+        if is_digit(a, i) {
+            return Ordering::Greater;
+        }
+        if is_digit(b, j) {
+            return Ordering::Less;
+        }
+        if first_diff != Ordering::Equal {
+            return first_diff;
+        }
+    }
+
+    Ordering::Equal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Ordering::{Equal, Greater, Less};
+
+    /// Comparisons from dpkg's test suite (`lib/dpkg/t/t-version.c` and
+    /// `scripts/t/Dpkg_Version.t`).
+    const COMPARISONS: &[(&str, &str, Ordering)] = &[
+        // Equality, including leading zeroes and implicit epochs and revisions.
+        ("0", "0", Equal),
+        ("0-00", "00-0", Equal),
+        ("1:2-3", "1:2-3", Equal),
+        ("1.0", "1.0", Equal),
+        ("1.0-1", "1.0-1", Equal),
+        ("0:1.0", "1.0", Equal),
+        ("1.0-0", "1.0", Equal),
+        ("1.01", "1.1", Equal),
+        ("0:1.18.36", "1.18.36", Equal),
+        ("1.0~rc1", "1.0~rc1", Equal),
+        ("0-pre", "0-pre", Equal),
+        ("0:0:0-0", "0:0:0-0", Equal),
+        // Epochs.
+        ("0:0-0", "1:0-0", Less),
+        ("1:0-0", "0:0-0", Greater),
+        ("1:1.0", "1.0", Greater),
+        ("1.0", "1:1.0", Less),
+        ("2:1.0", "1:2.0", Greater),
+        ("10.3", "1:0.4", Less),
+        ("9:1.18.36:5.4-20", "10:0.5.1-22", Less),
+        ("9:1.18.36:5.4-20", "9:1.18.36:5.5-1", Less),
+        ("9:1.18.36:5.4-20", "9:1.18.37:4.3-22", Less),
+        // Upstream versions.
+        ("0:a-0", "0:b-0", Less),
+        ("0:b-0", "0:a-0", Greater),
+        ("1.0", "2.0", Less),
+        ("2.0", "1.0", Greater),
+        ("1.9", "1.10", Less),
+        ("1.10", "1.9", Greater),
+        ("1.2.3", "1.2.3.4", Less),
+        ("1.0a", "1.0", Greater),
+        ("1.0", "1.0a", Less),
+        ("1.0a", "1.0b", Less),
+        ("1.0+dfsg", "1.0", Greater),
+        ("1.0", "1.0+", Less),
+        ("1.0+", "1.0.", Less),
+        ("1.0a", "1.0+", Less),
+        ("1.18.36", "1.18.35", Greater),
+        ("0:1.18.36", "1.18.35", Greater),
+        ("1:3.0.5-2", "1:3.0.5.1", Less),
+        ("0.4a6-2", "0.4-1", Greater),
+        ("1.1.6r2-1", "1.1.6r-1", Greater),
+        ("2.6b2-1", "2.6b-2", Greater),
+        ("98.1p5-1", "98.1-pre2-b6-2", Less),
+        ("2.0.7pre1-4", "2.0.7r-1", Less),
+        ("0-pre", "0-pree", Less),
+        ("0:0-0-0", "0-0", Greater),
+        ("0:0:0-0", "0:0-0", Greater),
+        ("0:0:0:0-0", "0:0:0-0", Greater),
+        // Tildes sort before everything, even the end of the version.
+        ("1.0~rc1", "1.0", Less),
+        ("1.0", "1.0~rc1", Greater),
+        ("1.0~rc1", "1.0~rc2", Less),
+        ("1.0~~", "1.0~~a", Less),
+        ("1.0~~a", "1.0~", Less),
+        ("1.0~", "1.0", Less),
+        ("1.0~", "1.0~~", Greater),
+        ("2.2~rc-4", "2.2-1", Less),
+        ("2.2~rc-4", "2.2~rc-5", Less),
+        // Revisions.
+        ("0:0-a", "0:0-b", Less),
+        ("0:0-b", "0:0-a", Greater),
+        ("1.0-1", "1.0", Greater),
+        ("1.0", "1.0-1", Less),
+        ("1.0-1", "1.0-2", Less),
+        ("1:1.25-4", "1:1.25-8", Less),
+        ("1:1.2.13-3", "1:1.2.13-3.1", Less),
+        ("1.18.36-0.17.35-18", "1.18.36-19", Greater),
+        ("1.0-1ubuntu1", "1.0-1", Greater),
+        ("1.0-1~bpo1", "1.0-1", Less),
+        ("1.0-1+b1", "1.0-1", Greater),
+    ];
+
+    #[test]
+    fn compares_like_dpkg() {
+        for &(a, b, expected) in COMPARISONS {
+            assert_eq!(compare(a, b).unwrap(), expected, "{a} vs {b}");
+            assert_eq!(compare(b, a).unwrap(), expected.reverse(), "{b} vs {a}");
+        }
+    }
+
+    #[test]
+    fn parses_components() {
+        let version: Version = "1:2.30-4ubuntu1".parse().unwrap();
+        assert_eq!(version.epoch, 1);
+        assert_eq!(version.upstream, "2.30");
+        assert_eq!(version.revision, "4ubuntu1");
+        assert_eq!(version.to_string(), "1:2.30-4ubuntu1");
+
+        // The revision starts after the last hyphen, the epoch ends at the first colon.
+        let version: Version = "0:1.18.36:5.4-0.17-20".parse().unwrap();
+        assert_eq!(version.epoch, 0);
+        assert_eq!(version.upstream, "1.18.36:5.4-0.17");
+        assert_eq!(version.revision, "20");
+        assert_eq!(version.to_string(), "1.18.36:5.4-0.17-20");
+
+        let version: Version = " 2.10 ".parse().unwrap();
+        assert_eq!((version.epoch, version.revision.as_str()), (0, ""));
+    }
+
+    #[test]
+    fn rejects_broken_versions() {
+        for version in [
+            "",
+            "  ",
+            "0:",
+            ":1.0",
+            "a:1.0",
+            "-1:1.0",
+            "99999999999999999999:1.0",
+            "1.0-",
+            "-1",
+            "0:-1",
+            "1.0 2.0",
+        ] {
+            assert!(version.parse::<Version>().is_err(), "{version:?}");
+        }
+    }
+}